@@ -0,0 +1,89 @@
+//! Roles of the accounts allowed to configure the Art contract, besides its admin.
+use stylus_sdk::alloy_primitives::Address;
+
+/// Role of the accounts allowed to configure the art: its parameters, generators, stages, tiers, cache and canvas
+pub const ROLE_ART_MANAGER: u8 = 1;
+
+/// Role of the accounts allowed to register the ERC-721 collections served by the Art contract
+pub const ROLE_COLLECTION_MANAGER: u8 = 2;
+
+/// All the roles, as bit flags
+pub const ALL_ROLES: u8 = ROLE_ART_MANAGER | ROLE_COLLECTION_MANAGER;
+
+/// Whether `role` is a single known role
+pub fn is_valid_role(role: u8) -> bool {
+    role.is_power_of_two() && role & ALL_ROLES == role
+}
+
+/// Address of the only account allowed to initialize the contract, set at build time with the `ART_ADMIN`
+/// environment variable (the build fails if it's not an address). The build also fails if it's not set,
+/// unless the `unsafe-any-initializer` feature is enabled: the first account to initialize the contract
+/// then becomes its admin, which is only meant for the tests and local tools.
+#[cfg(not(feature = "unsafe-any-initializer"))]
+pub const DESIGNATED_ADMIN: Option<Address> = Some(parse_address(env!(
+    "ART_ADMIN",
+    "ART_ADMIN must be set to the address of the account that will initialize the Art contract \
+     (enable the `unsafe-any-initializer` feature to let the first caller of initialize become its admin)"
+)));
+
+/// Address of the only account allowed to initialize the contract, if the `ART_ADMIN` environment variable
+/// was set at build time (the build fails if it's not an address). Otherwise, the first account to initialize
+/// the contract becomes its admin.
+#[cfg(feature = "unsafe-any-initializer")]
+pub const DESIGNATED_ADMIN: Option<Address> = match option_env!("ART_ADMIN") {
+    Some(admin) => Some(parse_address(admin)),
+    None => None,
+};
+
+/// Parses a hex address, with or without `0x` prefix, at compile time (panicking makes the build fail)
+const fn parse_address(address: &str) -> Address {
+    let digits = match address.as_bytes() {
+        [b'0', b'x' | b'X', digits @ ..] => digits,
+        digits => digits,
+    };
+    if digits.len() != 40 {
+        panic!("ART_ADMIN must be an address of 40 hex digits");
+    }
+    let mut bytes = [0u8; 20];
+    let mut index = 0;
+    while index < 20 {
+        bytes[index] = hex_digit(digits[2 * index]) << 4 | hex_digit(digits[2 * index + 1]);
+        index += 1;
+    }
+    Address::new(bytes)
+}
+
+/// Returns the value of a hex digit
+const fn hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        b'A'..=b'F' => digit - b'A' + 10,
+        _ => panic!("ART_ADMIN must be an address of 40 hex digits"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stylus_sdk::alloy_primitives::address;
+
+    #[test]
+    fn parses_addresses_with_or_without_prefix() {
+        let expected = address!("A6E41fFD769491a42A6e5Ce453259b93983a22EF");
+        assert_eq!(parse_address("0xA6E41fFD769491a42A6e5Ce453259b93983a22EF"), expected);
+        assert_eq!(parse_address("a6e41ffd769491a42a6e5ce453259b93983a22ef"), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_short_addresses() {
+        parse_address("0xA6E41fFD769491a42A6e5Ce453259b93983a22");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_non_hex_digits() {
+        parse_address("0xA6E41fFD769491a42A6e5Ce453259b93983a22EG");
+    }
+}
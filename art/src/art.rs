@@ -1,8 +1,10 @@
 //! Utilities.
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::fmt::Write;
 use hex_literal::hex;
 
 /// Represents a cell on the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Cell {
     x: usize,
     y: usize,
@@ -12,6 +14,22 @@ impl Cell {
     pub fn new(x: usize, y: usize) -> Cell {
         Cell { x, y }
     }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    /// Returns the cell moved by `dx` columns and `dy` rows (saturating at 0)
+    pub fn offset(&self, dx: isize, dy: isize) -> Cell {
+        Cell {
+            x: self.x.saturating_add_signed(dx),
+            y: self.y.saturating_add_signed(dy),
+        }
+    }
 }
 
 /// Represents an RGB color
@@ -31,7 +49,7 @@ impl Color {
         }
     }
 
-    pub const fn to_hex(&self) -> usize {
+    pub const fn to_hex(self) -> usize {
         (self.red as usize) << 16 | (self.green as usize) << 8 | self.blue as usize
     }
 }
@@ -78,6 +96,63 @@ pub fn zlib_format(mut data: &[u8]) -> Vec<u8> {
     out
 }
 
+/// Returns the unfiltered PNG scanlines of a `width` by `height` grid of pixels,
+/// with each pixel drawn as a `scale` by `scale` square
+pub fn raw_scanlines(
+    width: usize,
+    height: usize,
+    scale: usize,
+    pixel_at: impl Fn(usize, usize) -> Color,
+) -> Vec<u8> {
+    let line_len = 1 + width * scale * 3;
+    let mut out = Vec::with_capacity(height * scale * line_len);
+    let mut line = Vec::with_capacity(line_len);
+    for y in 0..height {
+        line.clear();
+        line.push(0); // Filter type: none
+        for x in 0..width {
+            let pixel = pixel_at(x, y);
+            for _ in 0..scale {
+                line.push(pixel.red);
+                line.push(pixel.green);
+                line.push(pixel.blue);
+            }
+        }
+        for _ in 0..scale {
+            out.extend_from_slice(&line);
+        }
+    }
+    out
+}
+
+/// Returns the bytes of a truecolour PNG image from its unfiltered scanlines
+pub fn encode_png(width: usize, height: usize, scanlines: &[u8]) -> Vec<u8> {
+    let idat = zlib_format(scanlines);
+    let mut out = Vec::new();
+    out.extend(hex!("89504E470D0A1A0A")); // PNG signature
+    let mut append_chunk = |name: &[u8; 4], chunk: &[u8]| {
+        out.extend((chunk.len() as u32).to_be_bytes());
+        let start = out.len();
+        out.extend(name);
+        out.extend(chunk);
+        let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+        out.extend(crc.checksum(&out[start..]).to_be_bytes());
+    };
+    let mut ihdr = Vec::new();
+    ihdr.extend((width as u32).to_be_bytes());
+    ihdr.extend((height as u32).to_be_bytes());
+    ihdr.push(8); // bit depth
+    ihdr.push(2); // colour type: truecolour
+    ihdr.push(0); // compression: deflate
+    ihdr.push(0); // filter method: adapative
+    ihdr.push(0); // interlace: no interlace
+    append_chunk(b"IHDR", &ihdr);
+    drop(ihdr);
+    append_chunk(b"IDAT", &idat);
+    append_chunk(b"IEND", &[]);
+    out
+}
+
 // Drawing algorithms are from http://members.chello.at/~easyfilter/Bresenham.pdf
 impl<const R: usize, const C: usize> Image<R, C> {
     /// Creates a new image with a default background color.
@@ -207,44 +282,115 @@ impl<const R: usize, const C: usize> Image<R, C> {
         }
     }
 
-    fn uncompressed_pixel_data(&self) -> Vec<u8> {
-        let mut out = Vec::with_capacity(R * (1 + C * 3));
-        for row in &*self.pixels {
-            out.push(0); // Filter type: none
-            for pixel in row {
-                out.push(pixel.red);
-                out.push(pixel.green);
-                out.push(pixel.blue);
+    /// Returns the bytes of the PNG formatted image 
+    pub fn make_png(&self) -> Vec<u8> {
+        self.make_scaled_png(1)
+    }
+
+    /// Returns the bytes of the PNG formatted image, with each pixel drawn as a `scale` by `scale` square
+    pub fn make_scaled_png(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let scanlines = raw_scanlines(C, R, scale, |x, y| self.pixels[y][x]);
+        encode_png(C * scale, R * scale, &scanlines)
+    }
+
+    /// Returns the red, green and blue bytes of the pixels, row by row
+    pub fn make_rgb(&self) -> Vec<u8> {
+        self.pixels.iter().flatten().flat_map(|pixel| [pixel.red, pixel.green, pixel.blue]).collect()
+    }
+
+    /// Returns the SVG document of the image, with each pixel drawn as a `scale` by `scale` square
+    pub fn make_svg(&self, scale: usize) -> String {
+        let scale = scale.max(1);
+        let mut svg = String::with_capacity(R * C * 64);
+        let _ = write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {C} {R}\" shape-rendering=\"crispEdges\">",
+            C * scale,
+            R * scale,
+        );
+        for (y, row) in self.pixels.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"1\" height=\"1\" fill=\"#{:06x}\"/>",
+                    pixel.to_hex(),
+                );
+            }
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Color of a pixel of the test images, different for each pixel of a 32 by 32 image
+    fn pattern(x: usize, y: usize) -> Color {
+        Color {
+            red: (x * 8) as u8,
+            green: (y * 8) as u8,
+            blue: (x ^ y) as u8,
+        }
+    }
+
+    fn pattern_image() -> Image<32, 32> {
+        let mut image = Image::new(Color::default());
+        for (y, row) in image.pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = pattern(x, y);
             }
         }
-        out
+        image
     }
 
-    /// Returns the bytes of the PNG formatted image 
-    pub fn make_png(&self) -> Vec<u8> {
-        let idat = zlib_format(&self.uncompressed_pixel_data());
-        let mut out = Vec::new();
-        out.extend(hex!("89504E470D0A1A0A")); // PNG signature
-        let mut append_chunk = |name: &[u8; 4], chunk: &[u8]| {
-            out.extend((chunk.len() as u32).to_be_bytes());
-            let start = out.len();
-            out.extend(name);
-            out.extend(chunk);
-            let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
-            out.extend(crc.checksum(&out[start..]).to_be_bytes());
-        };
-        let mut ihdr = Vec::new();
-        ihdr.extend((C as u32).to_be_bytes());
-        ihdr.extend((R as u32).to_be_bytes());
-        ihdr.push(8); // bit depth
-        ihdr.push(2); // colour type: truecolour
-        ihdr.push(0); // compression: deflate
-        ihdr.push(0); // filter method: adapative
-        ihdr.push(0); // interlace: no interlace
-        append_chunk(b"IHDR", &ihdr);
-        drop(ihdr);
-        append_chunk(b"IDAT", &idat);
-        append_chunk(b"IEND", &[]);
-        out
+    /// Decodes a PNG image with the reference decoder into its width, height and RGB bytes
+    fn decode_png(bytes: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+        let mut rgb = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut rgb).unwrap();
+        assert_eq!((info.color_type, info.bit_depth), (png::ColorType::Rgb, png::BitDepth::Eight));
+        rgb.truncate(info.buffer_size());
+        (info.width, info.height, rgb)
+    }
+
+    #[test]
+    fn rgb_lists_the_pixels_row_by_row() {
+        let rgb = pattern_image().make_rgb();
+        assert_eq!(rgb.len(), 32 * 32 * 3);
+        for (index, pixel) in rgb.chunks_exact(3).enumerate() {
+            let color = pattern(index % 32, index / 32);
+            assert_eq!(pixel, [color.red, color.green, color.blue]);
+        }
+    }
+
+    #[test]
+    fn png_decodes_to_the_pixels() {
+        let image = pattern_image();
+        assert_eq!(decode_png(&image.make_png()), (32, 32, image.make_rgb()));
+    }
+
+    #[test]
+    fn scaled_png_decodes_to_the_scaled_pixels() {
+        // 8 times larger, the scanlines span several zlib blocks
+        let (width, height, rgb) = decode_png(&pattern_image().make_scaled_png(8));
+        assert_eq!((width, height), (256, 256));
+        for (index, pixel) in rgb.chunks_exact(3).enumerate() {
+            let color = pattern(index % 256 / 8, index / 256 / 8);
+            assert_eq!(pixel, [color.red, color.green, color.blue], "pixel {index}");
+        }
+    }
+
+    #[test]
+    fn png_decodes_at_the_zlib_block_boundary() {
+        // 15 scanlines of 1 + 1456 * 3 bytes fill a zlib block exactly, and one more needs a second block
+        for height in [14, 15, 16] {
+            let scanlines = raw_scanlines(1456, height, 1, |x, y| pattern(x % 32, y));
+            let (_, _, rgb) = decode_png(&encode_png(1456, height, &scanlines));
+            let expected: Vec<u8> = scanlines.chunks_exact(1 + 1456 * 3).flat_map(|line| &line[1..]).copied().collect();
+            assert_eq!(rgb, expected, "height {height}");
+        }
     }
 }
@@ -0,0 +1,153 @@
+//! Trait attributes of the generated art.
+use alloc::vec::Vec;
+use crate::rng::ArtRng;
+
+/// Names of the available backgrounds
+pub const BACKGROUNDS: [&str; 3] = ["Gradient", "Solid", "Inverted"];
+
+/// Names of the available eyes
+pub const EYES: [&str; 3] = ["Lines", "Dots", "Wide"];
+
+/// Names of the available mouths
+pub const MOUTHS: [&str; 3] = ["Smile", "Flat", "Frown"];
+
+/// Names of the available palettes
+pub const PALETTES: [&str; 4] = ["Random", "Warm", "Cool", "Mono"];
+
+/// Relative weights used to pick each trait.
+/// The higher the weight, the more common the trait is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RarityWeights {
+    pub background: [u32; BACKGROUNDS.len()],
+    pub eyes: [u32; EYES.len()],
+    pub mouth: [u32; MOUTHS.len()],
+    pub palette: [u32; PALETTES.len()],
+}
+
+/// Number of rarity weights, one per value of each trait
+pub const RARITY_WEIGHT_COUNT: usize = BACKGROUNDS.len() + EYES.len() + MOUTHS.len() + PALETTES.len();
+
+impl RarityWeights {
+    /// Returns the weights of the values of each trait, or `None` if a trait doesn't have one weight per value
+    /// or the weights are not valid (see [`RarityWeights::is_valid`])
+    pub fn new(background: &[u32], eyes: &[u32], mouth: &[u32], palette: &[u32]) -> Option<RarityWeights> {
+        let weights = RarityWeights {
+            background: background.try_into().ok()?,
+            eyes: eyes.try_into().ok()?,
+            mouth: mouth.try_into().ok()?,
+            palette: palette.try_into().ok()?,
+        };
+        weights.is_valid().then_some(weights)
+    }
+
+    /// Returns true if some value of each trait can be picked, and the weights of each trait add up to a `u32`
+    pub fn is_valid(&self) -> bool {
+        let can_pick = |weights: &[u32]| {
+            weights.iter().try_fold(0u32, |total, &weight| total.checked_add(weight)).is_some_and(|total| total != 0)
+        };
+        can_pick(&self.background) && can_pick(&self.eyes) && can_pick(&self.mouth) && can_pick(&self.palette)
+    }
+
+    /// Returns the weights of the backgrounds, eyes, mouths and palettes, one after the other
+    pub fn to_vec(&self) -> Vec<u32> {
+        [&self.background[..], &self.eyes, &self.mouth, &self.palette].concat()
+    }
+
+    /// Reads the weights of the backgrounds, eyes, mouths and palettes, one after the other
+    pub fn from_slice(weights: &[u32; RARITY_WEIGHT_COUNT]) -> RarityWeights {
+        let (background, rest) = weights.split_at(BACKGROUNDS.len());
+        let (eyes, rest) = rest.split_at(EYES.len());
+        let (mouth, palette) = rest.split_at(MOUTHS.len());
+        RarityWeights {
+            background: background.try_into().unwrap(),
+            eyes: eyes.try_into().unwrap(),
+            mouth: mouth.try_into().unwrap(),
+            palette: palette.try_into().unwrap(),
+        }
+    }
+}
+
+/// Rarity weights used by the Art contract until the admin changes them
+pub const DEFAULT_RARITY_WEIGHTS: RarityWeights = RarityWeights {
+    background: [60, 30, 10],
+    eyes: [50, 35, 15],
+    mouth: [60, 25, 15],
+    palette: [55, 20, 20, 5],
+};
+
+/// Set of traits of a token. Each field is an index into the respective names array.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Traits {
+    pub background: u8,
+    pub eyes: u8,
+    pub mouth: u8,
+    pub palette: u8,
+}
+
+/// Picks an index of `weights`, with a probability proportional to its weight
+fn pick_weighted(rng: &mut ArtRng, weights: &[u32]) -> u8 {
+    let total: u32 = weights.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let mut roll = rng.below(total);
+    for (index, &weight) in weights.iter().enumerate() {
+        if roll < weight {
+            return index as u8;
+        }
+        roll -= weight;
+    }
+    0
+}
+
+impl Traits {
+    /// Picks a set of traits using the seeded `rng`
+    pub fn generate(rng: &mut ArtRng, weights: &RarityWeights) -> Traits {
+        Traits {
+            background: pick_weighted(rng, &weights.background),
+            eyes: pick_weighted(rng, &weights.eyes),
+            mouth: pick_weighted(rng, &weights.mouth),
+            palette: pick_weighted(rng, &weights.palette),
+        }
+    }
+
+    /// Returns the (trait type, value) pairs describing this set of traits
+    pub fn attributes(&self) -> [(&'static str, &'static str); 4] {
+        [
+            ("Background", BACKGROUNDS[self.background as usize]),
+            ("Eyes", EYES[self.eyes as usize]),
+            ("Mouth", MOUTHS[self.mouth as usize]),
+            ("Palette", PALETTES[self.palette as usize]),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_round_trip_through_storage_order() {
+        let weights = DEFAULT_RARITY_WEIGHTS.to_vec();
+        assert_eq!(weights.len(), RARITY_WEIGHT_COUNT);
+        assert_eq!(weights[..3], [60, 30, 10]);
+        assert_eq!(weights[9..], [55, 20, 20, 5]);
+        assert_eq!(RarityWeights::from_slice(&weights.try_into().unwrap()), DEFAULT_RARITY_WEIGHTS);
+    }
+
+    #[test]
+    fn validates_the_weights_of_each_trait() {
+        let RarityWeights { background, eyes, mouth, palette } = DEFAULT_RARITY_WEIGHTS;
+        assert_eq!(RarityWeights::new(&background, &eyes, &mouth, &palette), Some(DEFAULT_RARITY_WEIGHTS));
+        // Some weights may be zero, to never pick a value
+        assert!(RarityWeights::new(&[0, 1, 0], &eyes, &mouth, &palette).is_some());
+        // All the weights of a trait can't be zero
+        assert_eq!(RarityWeights::new(&background, &[0, 0, 0], &mouth, &palette), None);
+        // One weight per value
+        assert_eq!(RarityWeights::new(&background, &eyes, &[1, 1], &palette), None);
+        assert_eq!(RarityWeights::new(&background, &eyes, &mouth, &[1, 1, 1, 1, 1]), None);
+        // The sum of the weights of a trait must fit in a u32
+        assert_eq!(RarityWeights::new(&background, &eyes, &mouth, &[u32::MAX, 1, 0, 0]), None);
+        assert!(RarityWeights::new(&background, &eyes, &mouth, &[u32::MAX, 0, 0, 0]).is_some());
+    }
+}
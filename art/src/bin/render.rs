@@ -0,0 +1,293 @@
+//! Renders the art of tokens off-chain, without deploying the contracts.
+//!
+//! Usage: `cargo run --bin render -- [options] <token ids>`
+//!
+//! Token ids can be single ids (`7`), inclusive ranges (`0..=9`), exclusive ranges (`0..10`),
+//! or a comma-separated list of both (`1,4,10..20`).
+use std::{fs, path::PathBuf, process, str::FromStr};
+
+use stylus_sdk::alloy_primitives::{hex, Address, B256, U256};
+use stylus_workshop_rust_nft_art::{
+    config::ArtConfig,
+    gallery::{make_contact_sheet, ContactSheetOptions},
+    gen_art,
+    generators::{ArtGenerator, LegacyGenerator, BUILTIN_GENERATORS},
+    legacy_token_seed,
+    options::ArtOptions,
+    placeholder::make_placeholder,
+    revealed_token_seed,
+    rng::Seed,
+    stages::MAX_STAGE,
+    tiers::MAX_TIER,
+    utils::FnvHasher,
+    LATEST_ART_VERSION,
+};
+
+const USAGE: &str = "\
+Usage: render [options] <token ids>
+
+Renders the art of tokens to PNG and/or SVG files.
+
+Token ids:
+  7, 0..10, 0..=9 or a comma-separated list of them (e.g. 1,4,10..20)
+
+Options:
+  --owner <address>      Owner of the tokens, used to derive the seed of art version 1 tokens
+                         (default: the zero address)
+  --seed <bytes32>       Seed revealed for the token (`getTokenSeed` in the Art contract).
+                         Renders the token with art version 2 or later instead of version 1
+  --art-version <n>      Art version of the revealed seed (default: the latest version)
+  --style <id>           Built-in generator to use: 0 = Face, 1 = Stripes, 2 = Cyclops
+                         (default: the original face for art version 1 tokens, and otherwise picked from
+                         the token id, as a freshly deployed Art contract does)
+  --stage <n>            Stage unlocked by holding the tokens: 0 = Fresh, 1 = Settled, 2 = Seasoned,
+                         3 = Legendary (default: 0)
+  --tier <n>             Tier of the ERC-20 balance of the owner: 0 = None, 1 = Bronze, 2 = Silver,
+                         3 = Diamond (default: 0)
+  --options <hex>        Art options chosen by the owner (`setArtOptions` in the NFT contract), as up to
+                         3 bytes: palette + 1, background + 1 and accessory flags (e.g. 0x020103)
+  --placeholder          Renders the placeholder shown before the collection is revealed instead of the art
+  --format <format>      png, svg or both (default: png)
+  --scale <n>            Size in pixels of each pixel of the art (default: 8)
+  --out <dir>            Output directory (default: the current directory)
+  --contact-sheet <n>    Renders all the tokens into a single contact-sheet.png, in rows of n tokens
+  --border <n>           Width in pixels of the borders of the contact sheet (default: 1)
+  --no-labels            Doesn't label the tokens of the contact sheet with their ids
+  -h, --help             Prints this message";
+
+/// Output formats of the renderer
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Png,
+    Svg,
+    Both,
+}
+
+/// Options of the renderer
+struct Options {
+    owner: Address,
+    seed: Option<B256>,
+    art_version: u8,
+    style: Option<usize>,
+    stage: u8,
+    tier: u8,
+    art_options: ArtOptions,
+    placeholder: bool,
+    format: Format,
+    scale: usize,
+    out: PathBuf,
+    contact_sheet: Option<ContactSheetOptions>,
+    token_ids: Vec<U256>,
+}
+
+/// Parses a token id, a range of token ids or a comma-separated list of them
+fn parse_token_ids(value: &str, token_ids: &mut Vec<U256>) -> Result<(), String> {
+    for part in value.split(',').filter(|part| !part.is_empty()) {
+        let parse = |id: &str| {
+            U256::from_str(id.trim()).map_err(|_| format!("invalid token id: {id}"))
+        };
+        if let Some((start, end)) = part.split_once("..") {
+            let start = parse(start)?;
+            let end = match end.strip_prefix('=') {
+                Some(end) => parse(end)?.saturating_add(U256::from(1)),
+                None => parse(end)?,
+            };
+            let mut token_id = start;
+            while token_id < end {
+                token_ids.push(token_id);
+                token_id += U256::from(1);
+            }
+        } else {
+            token_ids.push(parse(part)?);
+        }
+    }
+    Ok(())
+}
+
+/// Parses the command line arguments
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        owner: Address::ZERO,
+        seed: None,
+        art_version: LATEST_ART_VERSION,
+        style: None,
+        stage: 0,
+        tier: 0,
+        art_options: ArtOptions::default(),
+        placeholder: false,
+        format: Format::Png,
+        scale: 8,
+        out: PathBuf::from("."),
+        contact_sheet: None,
+        token_ids: Vec::new(),
+    };
+    let mut sheet_options = ContactSheetOptions::default();
+    let mut sheet_columns = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {arg}"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            "--owner" => {
+                let owner = value()?;
+                options.owner = Address::from_str(&owner).map_err(|_| format!("invalid address: {owner}"))?;
+            }
+            "--seed" => {
+                let seed = value()?;
+                options.seed = Some(B256::from_str(&seed).map_err(|_| format!("invalid seed: {seed}"))?);
+            }
+            "--art-version" => {
+                let version = value()?;
+                options.art_version = match version.parse() {
+                    Ok(version @ 2..=LATEST_ART_VERSION) => version,
+                    _ => return Err(format!("invalid art version for a revealed seed: {version}")),
+                };
+            }
+            "--style" => {
+                let style = value()?;
+                options.style = match style.parse() {
+                    Ok(style) if style < BUILTIN_GENERATORS.len() => Some(style),
+                    _ => return Err(format!("invalid style: {style}")),
+                };
+            }
+            "--stage" => {
+                let stage = value()?;
+                options.stage = match stage.parse() {
+                    Ok(stage) if stage <= MAX_STAGE => stage,
+                    _ => return Err(format!("invalid stage: {stage}")),
+                };
+            }
+            "--tier" => {
+                let tier = value()?;
+                options.tier = match tier.parse() {
+                    Ok(tier) if tier <= MAX_TIER => tier,
+                    _ => return Err(format!("invalid tier: {tier}")),
+                };
+            }
+            "--options" => {
+                let art_options = value()?;
+                options.art_options = hex::decode(&art_options)
+                    .ok()
+                    .and_then(|bytes| ArtOptions::decode(&bytes))
+                    .ok_or_else(|| format!("invalid art options: {art_options}"))?;
+            }
+            "--placeholder" => options.placeholder = true,
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "png" => Format::Png,
+                    "svg" => Format::Svg,
+                    "both" => Format::Both,
+                    format => return Err(format!("invalid format: {format}")),
+                };
+            }
+            "--scale" => {
+                let scale = value()?;
+                options.scale = match scale.parse() {
+                    Ok(scale) if scale > 0 => scale,
+                    _ => return Err(format!("invalid scale: {scale}")),
+                };
+            }
+            "--out" => options.out = PathBuf::from(value()?),
+            "--contact-sheet" => {
+                let columns = value()?;
+                sheet_columns = match columns.parse() {
+                    Ok(columns) if columns > 0 => Some(columns),
+                    _ => return Err(format!("invalid number of columns: {columns}")),
+                };
+            }
+            "--border" => {
+                let border = value()?;
+                sheet_options.border = border.parse().map_err(|_| format!("invalid border: {border}"))?;
+            }
+            "--no-labels" => sheet_options.labels = false,
+            arg if arg.starts_with('-') => return Err(format!("unknown option: {arg}")),
+            ids => parse_token_ids(ids, &mut options.token_ids)?,
+        }
+    }
+    if options.token_ids.is_empty() {
+        return Err("no token ids given".to_string());
+    }
+    if let Some(columns) = sheet_columns {
+        if options.format != Format::Png {
+            return Err("contact sheets can only be rendered as PNG".to_string());
+        }
+        options.contact_sheet = Some(ContactSheetOptions { columns, ..sheet_options });
+    }
+    Ok(options)
+}
+
+/// Picks the style of a token from its id, as the Art contract does with the built-in generators registered
+fn default_style(token_id: U256) -> usize {
+    let mut hasher = FnvHasher::new();
+    hasher.update(token_id.as_le_slice());
+    (hasher.output() % BUILTIN_GENERATORS.len() as u64) as usize
+}
+
+fn render(options: &Options) -> Result<(), String> {
+    fs::create_dir_all(&options.out)
+        .map_err(|err| format!("cannot create {}: {err}", options.out.display()))?;
+    let config = ArtConfig::default();
+    let mut tiles = Vec::new();
+    for &token_id in &options.token_ids {
+        let seed = match options.seed {
+            Some(seed) => revealed_token_seed(options.art_version, seed),
+            None => Seed::Legacy(legacy_token_seed(options.owner, token_id)),
+        };
+        let generator: &dyn ArtGenerator = match (options.style, options.seed) {
+            (Some(style), _) => BUILTIN_GENERATORS[style],
+            (None, Some(_)) => BUILTIN_GENERATORS[default_style(token_id)],
+            // Version 1 tokens were not registered at mint time, so the contract draws them with the legacy generator
+            (None, None) => &LegacyGenerator,
+        };
+        let (image, description) = if options.placeholder {
+            (make_placeholder(&config), "Placeholder".to_string())
+        } else {
+            let (image, traits) = gen_art(&seed, generator, &config, &options.art_options, None, options.stage, options.tier);
+            let attributes: Vec<String> = traits
+                .attributes()
+                .iter()
+                .copied()
+                .chain(options.art_options.accessory_names().map(|name| ("Accessory", name)))
+                .map(|(trait_type, value)| format!("{trait_type}: {value}"))
+                .collect();
+            (image, format!("Style: {}, {}", generator.name(), attributes.join(", ")))
+        };
+
+        if options.contact_sheet.is_some() {
+            println!("{token_id}: {description}");
+            tiles.push((token_id.to_string(), image));
+            continue;
+        }
+        let mut files = Vec::new();
+        if options.format != Format::Svg {
+            files.push((options.out.join(format!("{token_id}.png")), image.make_scaled_png(options.scale)));
+        }
+        if options.format != Format::Png {
+            files.push((options.out.join(format!("{token_id}.svg")), image.make_svg(options.scale).into_bytes()));
+        }
+        for (path, bytes) in files {
+            fs::write(&path, bytes).map_err(|err| format!("cannot write {}: {err}", path.display()))?;
+            println!("{}", path.display());
+        }
+        println!("  {description}");
+    }
+
+    if let Some(sheet_options) = &options.contact_sheet {
+        let path = options.out.join("contact-sheet.png");
+        let sheet = make_contact_sheet(&tiles, sheet_options);
+        fs::write(&path, sheet.make_png(options.scale))
+            .map_err(|err| format!("cannot write {}: {err}", path.display()))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = parse_args(std::env::args().skip(1)).and_then(|options| render(&options));
+    if let Err(err) = result {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,53 @@
+//! Breeding: the art of a child token inherits the traits of its two parents.
+use alloc::vec::Vec;
+use stylus_sdk::{alloy_primitives::B256, crypto};
+use crate::attributes::Traits;
+use crate::rng::ArtRng;
+
+/// Percentage of the traits of a child that mutate, keeping the one picked from its own seed instead of a parent's
+pub const MUTATION_RATE: u32 = 10;
+
+/// Derives the seed of a child from the seeds of its parents and the seed revealed for it, which brings
+/// the randomness of its mutations
+pub fn child_seed(parent_seeds: [B256; 2], revealed_seed: B256) -> B256 {
+    let mut seed_data = Vec::with_capacity(96);
+    seed_data.extend_from_slice(parent_seeds[0].as_slice());
+    seed_data.extend_from_slice(parent_seeds[1].as_slice());
+    seed_data.extend_from_slice(revealed_seed.as_slice());
+    crypto::keccak(seed_data)
+}
+
+/// Picks each trait of a child from one of its `parents`, or from the traits picked from its own seed (`own`)
+/// if the trait mutates
+pub fn inherit_traits(rng: &mut ArtRng, own: Traits, parents: &[Traits; 2]) -> Traits {
+    let mut pick = |own: u8, parent_trait: fn(&Traits) -> u8| {
+        if rng.below(100) < MUTATION_RATE {
+            own
+        } else {
+            parent_trait(&parents[rng.below(2) as usize])
+        }
+    };
+    Traits {
+        background: pick(own.background, |traits| traits.background),
+        eyes: pick(own.eyes, |traits| traits.eyes),
+        mouth: pick(own.mouth, |traits| traits.mouth),
+        palette: pick(own.palette, |traits| traits.palette),
+    }
+}
+
+/// Encodes the traits of the parents of a child, one byte per trait
+pub fn encode_parent_traits(parents: &[Traits; 2]) -> Vec<u8> {
+    parents
+        .iter()
+        .flat_map(|traits| [traits.background, traits.eyes, traits.mouth, traits.palette])
+        .collect()
+}
+
+/// Decodes the traits of the parents of a child, returning `None` if it was not bred
+pub fn decode_parent_traits(bytes: &[u8]) -> Option<[Traits; 2]> {
+    let traits = |bytes: &[u8]| Traits { background: bytes[0], eyes: bytes[1], mouth: bytes[2], palette: bytes[3] };
+    match bytes.len() {
+        8 => Some([traits(&bytes[..4]), traits(&bytes[4..])]),
+        _ => None,
+    }
+}
@@ -0,0 +1,252 @@
+//! Collaborative pixel canvas: the token holders paint it one pixel at a time, with the colors of a shared palette.
+//!
+//! The Art contract inherits the views of [`PixelCanvas`] and checks who can paint before calling its setters.
+use alloc::{string::String, vec::Vec};
+use stylus_sdk::{
+    prelude::*,
+    storage::{StorageArray, StorageMap, StorageU256, StorageU32, StorageU64},
+    alloy_primitives::{U256, U32, U64},
+};
+use crate::art::{Color, Image};
+use crate::{make_png_uri, InvalidCanvasPixel, StylusNftArtError};
+
+/// Side, in pixels, of the canvas
+pub const CANVAS_SIZE: usize = 64;
+
+/// Number of colors of the palette of the canvas
+pub const CANVAS_COLORS: usize = 16;
+
+/// Number of bits of the palette index of each pixel
+const BITS_PER_PIXEL: usize = 4;
+
+/// Number of pixels packed in each storage word
+const PIXELS_PER_WORD: usize = 256 / BITS_PER_PIXEL;
+
+/// Number of storage words holding the pixels
+const CANVAS_WORDS: usize = CANVAS_SIZE * CANVAS_SIZE / PIXELS_PER_WORD;
+
+/// Default palette of the canvas. The canvas starts filled with the first color.
+pub const DEFAULT_CANVAS_PALETTE: [u32; CANVAS_COLORS] = [
+    0xffffff, 0x000000, 0x7f7f7f, 0xc3c3c3, 0x880015, 0xed1c24, 0xff7f27, 0xfff200,
+    0x22b14c, 0xb5e61d, 0x00a2e8, 0x99d9ea, 0x3f48cc, 0x7092be, 0xa349a4, 0xffaec9,
+];
+
+/// Default number of seconds a token must wait between two pixels
+pub const DEFAULT_PAINT_COOLDOWN: u64 = 5 * 60;
+
+/// Pixels of the canvas, packed as 4-bit palette indexes, and the painting rules
+#[storage]
+pub struct PixelCanvas {
+    /// Palette indexes of the pixels, row by row, 64 pixels per word from the least significant bits
+    pixels: StorageArray<StorageU256, CANVAS_WORDS>,
+    /// Colors (0xRRGGBB) of the palette
+    palette: StorageArray<StorageU32, CANVAS_COLORS>,
+    /// Number of seconds a token must wait between two pixels
+    cooldown: StorageU64,
+    /// Token id to the timestamp of the last pixel it painted (0 if none)
+    last_painted: StorageMap<U256, StorageU64>,
+}
+
+/// Returns the storage word and the bit offset of a pixel
+fn pixel_position(x: usize, y: usize) -> (usize, usize) {
+    let index = y * CANVAS_SIZE + x;
+    (index / PIXELS_PER_WORD, (index % PIXELS_PER_WORD) * BITS_PER_PIXEL)
+}
+
+/// Returns the palette index of the pixel at the bit `offset` of a storage word
+fn word_pixel(word: U256, offset: usize) -> u8 {
+    ((word >> offset) & U256::from(CANVAS_COLORS - 1)).to::<u8>()
+}
+
+/// Returns a storage word with the palette index of the pixel at the bit `offset` replaced by `color`
+fn with_word_pixel(word: U256, offset: usize, color: u8) -> U256 {
+    let mask = U256::from(CANVAS_COLORS - 1) << offset;
+    (word & !mask) | (U256::from(color) << offset)
+}
+
+/// Whether (`x`, `y`) is a pixel of the canvas
+pub fn is_valid_canvas_pixel(x: u8, y: u8) -> bool {
+    (x as usize) < CANVAS_SIZE && (y as usize) < CANVAS_SIZE
+}
+
+// Helpers used by the Art contract, which checks who can paint
+impl PixelCanvas {
+    /// Returns the palette index of a pixel, which must be inside the canvas
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        let (word, offset) = pixel_position(x, y);
+        word_pixel(self.pixels.get(word).unwrap_or_default(), offset)
+    }
+
+    /// Sets the palette index of a pixel, which must be inside the canvas
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u8) {
+        let (word, offset) = pixel_position(x, y);
+        let mut setter = self.pixels.setter(word).unwrap();
+        let value = with_word_pixel(setter.get(), offset, color);
+        setter.set(value);
+    }
+
+    /// Replaces the colors of the palette
+    pub fn set_palette(&mut self, colors: &[u32; CANVAS_COLORS]) {
+        for (index, &color) in colors.iter().enumerate() {
+            self.palette.setter(index).unwrap().set(U32::from(color));
+        }
+    }
+
+    /// Sets the number of seconds a token must wait between two pixels
+    pub fn set_cooldown(&mut self, cooldown: u64) {
+        self.cooldown.set(U64::from(cooldown));
+    }
+
+    /// Returns the timestamp from which a token can paint again (0 if it never painted)
+    pub fn next_paint_time(&self, token_id: U256) -> u64 {
+        let last_painted = self.last_painted.get(token_id).to::<u64>();
+        if last_painted == 0 {
+            return 0;
+        }
+        last_painted.saturating_add(self.cooldown.get().to::<u64>())
+    }
+
+    /// Records that a token painted a pixel at `timestamp`
+    pub fn record_paint(&mut self, token_id: U256, timestamp: u64) {
+        self.last_painted.insert(token_id, U64::from(timestamp));
+    }
+
+    /// Renders the current state of the canvas
+    pub fn image(&self) -> Image<CANVAS_SIZE, CANVAS_SIZE> {
+        let palette: Vec<Color> = (0..CANVAS_COLORS)
+            .map(|index| Color::from_hex(self.palette.get(index).unwrap_or_default().to::<usize>()))
+            .collect();
+        let mut image = Image::new(palette[0]);
+        for word_index in 0..CANVAS_WORDS {
+            let word = self.pixels.get(word_index).unwrap_or_default();
+            // Untouched words keep the background
+            if word.is_zero() {
+                continue;
+            }
+            for pixel in 0..PIXELS_PER_WORD {
+                let color = word_pixel(word, pixel * BITS_PER_PIXEL);
+                let index = word_index * PIXELS_PER_WORD + pixel;
+                image.pixels[index / CANVAS_SIZE][index % CANVAS_SIZE] = palette[color as usize];
+            }
+        }
+        image
+    }
+}
+
+#[public]
+impl PixelCanvas {
+    /// Returns the `data:` URI of the PNG image of the current state of the canvas
+    #[selector(name = "canvasPNG")]
+    pub fn canvas_png(&self) -> Result<String, StylusNftArtError> {
+        Ok(make_png_uri(&self.image().make_png()))
+    }
+
+    /// Returns the palette index of a pixel of the canvas
+    pub fn get_canvas_pixel(&self, x: u8, y: u8) -> Result<u8, StylusNftArtError> {
+        if !is_valid_canvas_pixel(x, y) {
+            return Err(StylusNftArtError::InvalidCanvasPixel(InvalidCanvasPixel { x, y }));
+        }
+        Ok(self.pixel(x as usize, y as usize))
+    }
+
+    /// Returns the colors of the palette of the canvas
+    pub fn get_canvas_palette(&self) -> Result<Vec<u32>, StylusNftArtError> {
+        Ok((0..CANVAS_COLORS).map(|index| self.palette.get(index).unwrap_or_default().to::<u32>()).collect())
+    }
+
+    /// Returns the number of seconds a token must wait between two pixels
+    pub fn get_paint_cooldown(&self) -> Result<u64, StylusNftArtError> {
+        Ok(self.cooldown.get().to::<u64>())
+    }
+
+    /// Returns the timestamp from which a token can paint again (0 if it never painted)
+    pub fn get_next_paint_time(&self, token_id: U256) -> Result<u64, StylusNftArtError> {
+        Ok(self.next_paint_time(token_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pixels packed like the storage of [`PixelCanvas`], without the storage
+    struct Pixels([U256; CANVAS_WORDS]);
+
+    impl Pixels {
+        fn new() -> Pixels {
+            Pixels([U256::ZERO; CANVAS_WORDS])
+        }
+
+        fn get(&self, x: usize, y: usize) -> u8 {
+            let (word, offset) = pixel_position(x, y);
+            word_pixel(self.0[word], offset)
+        }
+
+        fn set(&mut self, x: usize, y: usize, color: u8) {
+            let (word, offset) = pixel_position(x, y);
+            self.0[word] = with_word_pixel(self.0[word], offset, color);
+        }
+    }
+
+    #[test]
+    fn pixels_are_packed_64_per_word_row_by_row() {
+        assert_eq!(CANVAS_WORDS, 64);
+        assert_eq!(pixel_position(0, 0), (0, 0));
+        assert_eq!(pixel_position(1, 0), (0, 4));
+        assert_eq!(pixel_position(63, 0), (0, 252));
+        assert_eq!(pixel_position(0, 1), (1, 0));
+        assert_eq!(pixel_position(63, 63), (63, 252));
+    }
+
+    #[test]
+    fn set_pixels_read_back_at_the_word_boundaries() {
+        let mut pixels = Pixels::new();
+        // The first and last pixels of consecutive words, which are the ends of consecutive rows
+        let boundaries = [(0, 0, 1), (63, 0, 15), (0, 1, 7), (62, 0, 9), (1, 1, 14), (63, 63, 15), (0, 63, 3)];
+        for &(x, y, color) in &boundaries {
+            pixels.set(x, y, color);
+        }
+        for &(x, y, color) in &boundaries {
+            assert_eq!(pixels.get(x, y), color, "pixel ({x}, {y})");
+        }
+        // The last pixel of a word uses its 4 most significant bits
+        assert_eq!(pixels.0[0] >> 252, U256::from(15));
+        assert_eq!(pixels.get(2, 0), 0);
+        assert_eq!(pixels.get(1, 63), 0);
+    }
+
+    #[test]
+    fn setting_a_pixel_leaves_its_neighbours() {
+        let mut pixels = Pixels::new();
+        for y in 0..CANVAS_SIZE {
+            for x in 0..CANVAS_SIZE {
+                pixels.set(x, y, ((x + y) % CANVAS_COLORS) as u8);
+            }
+        }
+        // Overwriting a pixel with a lower index clears its previous bits
+        pixels.set(31, 7, 0);
+        pixels.set(32, 7, 15);
+        for y in 0..CANVAS_SIZE {
+            for x in 0..CANVAS_SIZE {
+                let expected = match (x, y) {
+                    (31, 7) => 0,
+                    (32, 7) => 15,
+                    _ => ((x + y) % CANVAS_COLORS) as u8,
+                };
+                assert_eq!(pixels.get(x, y), expected, "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn only_pixels_inside_the_canvas_are_valid() {
+        assert!(is_valid_canvas_pixel(0, 0));
+        assert!(is_valid_canvas_pixel(63, 0));
+        assert!(is_valid_canvas_pixel(0, 63));
+        assert!(is_valid_canvas_pixel(63, 63));
+        assert!(!is_valid_canvas_pixel(64, 0));
+        assert!(!is_valid_canvas_pixel(0, 64));
+        assert!(!is_valid_canvas_pixel(64, 64));
+        assert!(!is_valid_canvas_pixel(u8::MAX, 10));
+    }
+}
@@ -0,0 +1,37 @@
+//! Registry of the ERC-721 collections the Art contract renders the art for, besides its own NFT contract.
+use alloc::vec::Vec;
+use stylus_sdk::{
+    prelude::*,
+    crypto,
+    storage::{StorageArray, StorageBool, StorageString, StorageU32, StorageU8},
+    alloy_primitives::{Address, U256},
+};
+use crate::rng::Seed;
+
+/// Registration and style of a collection: its name, its generator and the colors its art is drawn with
+#[storage]
+pub struct CollectionStyle {
+    /// Whether the admin has registered the collection
+    pub registered: StorageBool,
+    /// Generator id + 1 used by all its tokens (0 to pick one of the registered generators from each token id)
+    pub generator: StorageU8,
+    /// Name of its tokens in their metadata, followed by " #" and the token id
+    pub name: StorageString,
+    /// Number of generators registered when the collection was first registered, among which its tokens pick
+    /// their generator when it's not fixed
+    pub generator_count: StorageU8,
+    /// Whether its art is drawn with its own colors instead of the ones of the Art contract
+    pub custom_colors: StorageBool,
+    /// Its own background color and gradient stops (0xRRGGBB)
+    pub colors: StorageArray<StorageU32, 3>,
+}
+
+/// Derives the seed of a token of a registered collection from the collection address, the token id and its owner,
+/// so its art changes when it's transferred
+pub fn collection_token_seed(collection: Address, token_id: U256, owner: Address) -> Seed {
+    let mut seed_data = Vec::with_capacity(72);
+    seed_data.extend_from_slice(collection.as_slice());
+    seed_data.extend_from_slice(&token_id.to_be_bytes::<32>());
+    seed_data.extend_from_slice(owner.into_word().as_slice());
+    Seed::Portable(crypto::keccak(seed_data).0)
+}
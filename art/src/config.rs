@@ -0,0 +1,67 @@
+//! Parameters of the art generator.
+use alloc::vec::Vec;
+use crate::art::{Cell, Color};
+use crate::attributes::{RarityWeights, DEFAULT_RARITY_WEIGHTS};
+
+/// Number of palettes with configurable colors (all palettes but "Random")
+pub const CONFIGURABLE_PALETTES: usize = 3;
+
+/// Maximum number of colors of a palette
+pub const MAX_PALETTE_COLORS: usize = 16;
+
+/// Parameters used by [`crate::gen_art`] to draw the image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtConfig {
+    /// Color of the "Solid" background
+    pub background: Color,
+    /// First stop of the gradient backgrounds
+    pub gradient_start: Color,
+    /// Last stop of the gradient backgrounds
+    pub gradient_end: Color,
+    /// Colors of the configurable palettes (indexed by palette trait - 1)
+    pub palettes: [Vec<Color>; CONFIGURABLE_PALETTES],
+    /// Center of the left eye
+    pub left_eye: Cell,
+    /// Center of the right eye
+    pub right_eye: Cell,
+    /// Center of the mouth
+    pub mouth: Cell,
+    /// Background color of the placeholder of unrevealed tokens
+    pub placeholder_background: Color,
+    /// Color of the question mark of the placeholder of unrevealed tokens
+    pub placeholder_foreground: Color,
+    /// Relative weights used to pick the traits
+    pub rarity_weights: RarityWeights,
+}
+
+impl Default for ArtConfig {
+    fn default() -> Self {
+        let colors = |hex: [usize; 4]| hex.iter().map(|&hex| Color::from_hex(hex)).collect();
+        ArtConfig {
+            background: Color::from_hex(0xe3066e),
+            gradient_start: Color::from_hex(0xff0000),
+            gradient_end: Color::from_hex(0x0000ff),
+            palettes: [
+                colors([0xff5733, 0xffbd33, 0xff8c00, 0xc70039]),
+                colors([0x33c1ff, 0x3375ff, 0x00e5c0, 0x7d33ff]),
+                colors([0x000000, 0x404040, 0x808080, 0xffffff]),
+            ],
+            left_eye: Cell::new(4, 5),
+            right_eye: Cell::new(10, 5),
+            mouth: Cell::new(7, 9),
+            placeholder_background: Color::from_hex(0x202020),
+            placeholder_foreground: Color::from_hex(0xffffff),
+            rarity_weights: DEFAULT_RARITY_WEIGHTS,
+        }
+    }
+}
+
+/// Whether an eye can be drawn centered at (`x`, `y`) in a 32x32 image
+pub fn is_valid_eye_position(x: u8, y: u8) -> bool {
+    (1..=30).contains(&x) && (1..=30).contains(&y)
+}
+
+/// Whether a mouth can be drawn centered at (`x`, `y`) in a 32x32 image
+pub fn is_valid_mouth_position(x: u8, y: u8) -> bool {
+    (3..=28).contains(&x) && y <= 30
+}
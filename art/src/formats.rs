@@ -0,0 +1,61 @@
+//! Formats of the art returned as raw bytes by `generateArtAs`.
+use alloc::vec::Vec;
+use stylus_sdk::crypto;
+use crate::art::Image;
+
+/// Format of the raw art, selected by its id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtFormat {
+    /// PNG image (id 0)
+    Png,
+    /// Pixels as red, green and blue bytes, row by row (id 1)
+    Rgb,
+    /// keccak256 of the PNG image (id 2)
+    PngKeccak,
+    /// SVG document, with a rect per pixel (id 3, `svg` feature)
+    #[cfg(feature = "svg")]
+    Svg,
+    /// GIF image (id 4, `gif` feature)
+    #[cfg(feature = "gif")]
+    Gif,
+}
+
+/// Ids of the formats the contract was built with
+pub const SUPPORTED_FORMATS: &[u8] = &[
+    0,
+    1,
+    2,
+    #[cfg(feature = "svg")]
+    3,
+    #[cfg(feature = "gif")]
+    4,
+];
+
+impl ArtFormat {
+    /// Returns the format with id `id`, or `None` if the contract was built without it
+    pub fn from_id(id: u8) -> Option<ArtFormat> {
+        match id {
+            0 => Some(ArtFormat::Png),
+            1 => Some(ArtFormat::Rgb),
+            2 => Some(ArtFormat::PngKeccak),
+            #[cfg(feature = "svg")]
+            3 => Some(ArtFormat::Svg),
+            #[cfg(feature = "gif")]
+            4 => Some(ArtFormat::Gif),
+            _ => None,
+        }
+    }
+
+    /// Encodes the image in this format
+    pub fn encode(self, image: &Image<32, 32>) -> Vec<u8> {
+        match self {
+            ArtFormat::Png => image.make_png(),
+            ArtFormat::Rgb => image.make_rgb(),
+            ArtFormat::PngKeccak => crypto::keccak(image.make_png()).to_vec(),
+            #[cfg(feature = "svg")]
+            ArtFormat::Svg => image.make_svg(1).into_bytes(),
+            #[cfg(feature = "gif")]
+            ArtFormat::Gif => image.make_gif(),
+        }
+    }
+}
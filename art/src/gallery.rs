@@ -0,0 +1,274 @@
+//! Contact sheets: many tokens rendered into a single image.
+use alloc::{string::String, vec, vec::Vec};
+use crate::art::{encode_png, raw_scanlines, Color, Image};
+
+/// Side, in pixels, of the art of a token
+const TILE_SIZE: usize = 32;
+
+/// Width of a glyph of the label font, plus one column of spacing
+const GLYPH_WIDTH: usize = 4;
+
+/// Height of the label strip below each tile: the 5-row glyphs with one row of padding above and below
+const LABEL_HEIGHT: usize = 7;
+
+/// Number of glyphs of a label that fit below a tile
+pub const MAX_LABEL_CHARS: usize = TILE_SIZE / GLYPH_WIDTH;
+
+/// 3x5 font for the digits 0-9. Each row is 3 bits, the most significant one being the leftmost pixel.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// An image whose size is only known at runtime
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    /// Creates a new canvas filled with `bg_color`
+    pub fn new(width: usize, height: usize, bg_color: Color) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![bg_color; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`)
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Sets the color of the pixel at (`x`, `y`), ignoring pixels out of the canvas
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    /// Copies `image` with its top left corner at (`x`, `y`)
+    pub fn draw_image<const R: usize, const C: usize>(&mut self, image: &Image<R, C>, x: usize, y: usize) {
+        for (dy, row) in image.pixels.iter().enumerate() {
+            for (dx, &pixel) in row.iter().enumerate() {
+                self.set_pixel(x + dx, y + dy, pixel);
+            }
+        }
+    }
+
+    /// Draws the digits of `text` with the top left corner of the first glyph at (`x`, `y`).
+    /// Other characters are left blank.
+    pub fn draw_text(&mut self, text: &str, x: usize, y: usize, color: Color) {
+        for (index, c) in text.chars().enumerate() {
+            let Some(digit) = c.to_digit(10) else {
+                continue;
+            };
+            for (dy, row) in DIGITS[digit as usize].iter().enumerate() {
+                for dx in 0..3 {
+                    if row & (0b100 >> dx) != 0 {
+                        self.set_pixel(x + index * GLYPH_WIDTH + dx, y + dy, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the bytes of the PNG formatted canvas, with each pixel drawn as a `scale` by `scale` square
+    pub fn make_png(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let scanlines = raw_scanlines(self.width, self.height, scale, |x, y| self.pixel(x, y));
+        encode_png(self.width * scale, self.height * scale, &scanlines)
+    }
+}
+
+/// Layout of a contact sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactSheetOptions {
+    /// Number of tiles per row
+    pub columns: usize,
+    /// Width in pixels of the border around each tile
+    pub border: usize,
+    /// Color of the borders and of the label strips
+    pub border_color: Color,
+    /// Whether to draw the label of each tile below it
+    pub labels: bool,
+    /// Color of the labels
+    pub label_color: Color,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        ContactSheetOptions {
+            columns: 4,
+            border: 1,
+            border_color: Color::from_hex(0x000000),
+            labels: true,
+            label_color: Color::from_hex(0xffffff),
+        }
+    }
+}
+
+/// Renders the tiles in a grid, in rows of `options.columns` tiles, each labeled with its string.
+/// Labels only show digits, and only their last [`MAX_LABEL_CHARS`] characters fit below the tile:
+/// longer labels (token ids from 100000000) are cut to them, so they show the last 8 digits of the id.
+pub fn make_contact_sheet(tiles: &[(String, Image<32, 32>)], options: &ContactSheetOptions) -> Canvas {
+    let columns = options.columns.max(1);
+    let rows = tiles.len().div_ceil(columns).max(1);
+    let label_height = if options.labels { LABEL_HEIGHT } else { 0 };
+    let cell_width = TILE_SIZE + options.border;
+    let cell_height = TILE_SIZE + label_height + options.border;
+    let mut canvas = Canvas::new(
+        columns.min(tiles.len().max(1)) * cell_width + options.border,
+        rows * cell_height + options.border,
+        options.border_color,
+    );
+
+    for (index, (label, image)) in tiles.iter().enumerate() {
+        let x = options.border + (index % columns) * cell_width;
+        let y = options.border + (index / columns) * cell_height;
+        canvas.draw_image(image, x, y);
+        if options.labels {
+            let skipped = label.chars().count().saturating_sub(MAX_LABEL_CHARS);
+            let label: String = label.chars().skip(skipped).collect();
+            // The last glyph doesn't need its spacing column
+            let label_width = (label.chars().count() * GLYPH_WIDTH).saturating_sub(1);
+            canvas.draw_text(&label, x + (TILE_SIZE - label_width) / 2, y + TILE_SIZE + 1, options.label_color);
+        }
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BORDER: Color = Color::from_hex(0x000000);
+    const LABEL: Color = Color::from_hex(0xffffff);
+
+    /// Tiles filled with a different color each, labeled with their index
+    fn tiles(count: usize) -> Vec<(String, Image<32, 32>)> {
+        (0..count).map(|index| (index.to_string(), Image::new(tile_color(index)))).collect()
+    }
+
+    fn tile_color(index: usize) -> Color {
+        Color::from_hex(0x102030 + index * 0x101010)
+    }
+
+    /// Decodes the PNG of a contact sheet with the reference decoder into its width, height and pixels
+    fn decode_sheet(sheet: &Canvas) -> (usize, usize, Vec<Color>) {
+        let png = sheet.make_png(1);
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut rgb = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut rgb).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        rgb.truncate(info.buffer_size());
+        let pixels = rgb.chunks_exact(3).map(|rgb| Color { red: rgb[0], green: rgb[1], blue: rgb[2] }).collect();
+        (info.width as usize, info.height as usize, pixels)
+    }
+
+    /// Returns the pixels of a decoded sheet of `width` pixels in the rectangle from (`x`, `y`) of `w` by `h` pixels
+    fn rect(pixels: &[Color], width: usize, x: usize, y: usize, w: usize, h: usize) -> Vec<Color> {
+        (y..y + h).flat_map(|y| (x..x + w).map(move |x| pixels[y * width + x])).collect()
+    }
+
+    /// Returns the 3x5 pixels of the glyph of a digit, drawn over the border color
+    fn glyph(digit: usize) -> Vec<Color> {
+        DIGITS[digit]
+            .iter()
+            .flat_map(|row| (0..3).map(move |dx| if row & (0b100 >> dx) != 0 { LABEL } else { BORDER }))
+            .collect()
+    }
+
+    #[test]
+    fn sheet_size_depends_on_the_columns_and_tiles() {
+        let options = ContactSheetOptions::default();
+        // 6 tiles in rows of 4: 2 rows, each tile with its border and label strip
+        let (width, height, _) = decode_sheet(&make_contact_sheet(&tiles(6), &options));
+        assert_eq!((width, height), (4 * 33 + 1, 2 * 40 + 1));
+        // Fewer tiles than columns: the sheet is only as wide as the tiles
+        let (width, height, _) = decode_sheet(&make_contact_sheet(&tiles(3), &options));
+        assert_eq!((width, height), (3 * 33 + 1, 40 + 1));
+        // No tiles: a single empty cell
+        let (width, height, _) = decode_sheet(&make_contact_sheet(&[], &options));
+        assert_eq!((width, height), (33 + 1, 40 + 1));
+        let options = ContactSheetOptions { columns: 2, border: 0, ..options };
+        let (width, height, _) = decode_sheet(&make_contact_sheet(&tiles(5), &options));
+        assert_eq!((width, height), (2 * 32, 3 * 39));
+    }
+
+    #[test]
+    fn tiles_are_placed_in_rows_inside_their_borders() {
+        let options = ContactSheetOptions { columns: 3, border: 2, ..ContactSheetOptions::default() };
+        let (width, height, pixels) = decode_sheet(&make_contact_sheet(&tiles(5), &options));
+        assert_eq!((width, height), (3 * 34 + 2, 2 * 41 + 2));
+        for index in 0..5 {
+            let (x, y) = (2 + index % 3 * 34, 2 + index / 3 * 41);
+            assert_eq!(rect(&pixels, width, x, y, 32, 32), vec![tile_color(index); 32 * 32], "tile {index}");
+            // The border is drawn all around the tile
+            assert_eq!(rect(&pixels, width, x - 2, y - 2, 36, 2), vec![BORDER; 36 * 2], "top border of {index}");
+            assert_eq!(rect(&pixels, width, x - 2, y, 2, 32), vec![BORDER; 2 * 32], "left border of {index}");
+            assert_eq!(rect(&pixels, width, x + 32, y, 2, 32), vec![BORDER; 2 * 32], "right border of {index}");
+        }
+        // The missing last tile is left in the border color
+        assert_eq!(rect(&pixels, width, 2 + 2 * 34, 2 + 41, 32, 32), vec![BORDER; 32 * 32]);
+        assert_eq!(rect(&pixels, width, 0, height - 2, width, 2), vec![BORDER; width * 2]);
+    }
+
+    #[test]
+    fn labels_are_centered_below_the_tiles() {
+        let (width, _, pixels) = decode_sheet(&make_contact_sheet(&tiles(1), &ContactSheetOptions::default()));
+        // "0" is 3 pixels wide, centered 14 pixels from the left of the tile, one row below it
+        assert_eq!(rect(&pixels, width, 1 + 14, 1 + 33, 3, 5), glyph(0));
+        // Nothing else is drawn in the label strip
+        let strip = rect(&pixels, width, 1, 1 + 32, 32, LABEL_HEIGHT);
+        let count = |pixels: &[Color]| pixels.iter().filter(|&&pixel| pixel == LABEL).count();
+        assert_eq!(count(&strip), count(&glyph(0)));
+    }
+
+    #[test]
+    fn no_labels_leaves_no_label_strip() {
+        let options = ContactSheetOptions { columns: 2, labels: false, ..ContactSheetOptions::default() };
+        let (width, height, pixels) = decode_sheet(&make_contact_sheet(&tiles(4), &options));
+        assert_eq!((width, height), (2 * 33 + 1, 2 * 33 + 1));
+        // The tiles of the second row start right after the border below the first row
+        assert_eq!(rect(&pixels, width, 1, 33, 32, 1), vec![BORDER; 32]);
+        assert_eq!(rect(&pixels, width, 1, 34, 32, 32), vec![tile_color(2); 32 * 32]);
+        assert!(!pixels.contains(&LABEL));
+    }
+
+    #[test]
+    fn long_labels_show_their_last_digits() {
+        let options = ContactSheetOptions::default();
+        let label = |label: &str| {
+            let tile = [(String::from(label), Image::new(tile_color(0)))];
+            let (width, _, pixels) = decode_sheet(&make_contact_sheet(&tile, &options));
+            rect(&pixels, width, 1, 33, 32, LABEL_HEIGHT)
+        };
+        // 8 digits fill the width of the tile, from its left edge
+        let eight_digits = label("23456789");
+        assert_eq!(rect(&eight_digits, 32, 0, 1, 3, 5), glyph(2));
+        assert_eq!(rect(&eight_digits, 32, 28, 1, 3, 5), glyph(9));
+        // Longer labels are cut to their last 8 digits
+        assert_eq!(label("123456789"), eight_digits);
+        assert_eq!(label("100000000"), label("00000000"));
+        assert_ne!(label("12345678"), eight_digits);
+    }
+}
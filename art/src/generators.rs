@@ -0,0 +1,239 @@
+//! Art generators.
+use crate::art::{Cell, Color, Image};
+use crate::attributes::{RarityWeights, Traits};
+use crate::breeding::inherit_traits;
+use crate::config::ArtConfig;
+use crate::options::ArtOptions;
+use crate::rng::{ArtRng, Seed};
+
+/// Random stream of the seed used to inherit the traits of the parents
+const INHERITANCE_STREAM: u32 = 2;
+
+/// Generates the art of a token from its seed.
+pub trait ArtGenerator {
+    /// Name of the style drawn by the generator
+    fn name(&self) -> &'static str;
+
+    /// Picks the traits for a seed with the rarity `weights`, inheriting them from the `parents` of bred tokens,
+    /// and replacing them with the ones chosen in `options`
+    fn traits(&self, seed: &Seed, weights: &RarityWeights, options: &ArtOptions, parents: Option<&[Traits; 2]>) -> Traits {
+        pick_traits(&mut seed.rng(0), seed, weights, options, parents)
+    }
+
+    /// Generates the image and the traits for a seed, inheriting the traits of the `parents` of bred tokens
+    /// and honouring the traits chosen in `options`
+    fn generate(
+        &self,
+        seed: &Seed,
+        config: &ArtConfig,
+        options: &ArtOptions,
+        parents: Option<&[Traits; 2]>,
+    ) -> (Image<32, 32>, Traits);
+
+    /// Returns true if the accessories, the tier and the stage of the token are drawn over the generated image
+    fn draws_overlays(&self) -> bool {
+        true
+    }
+}
+
+/// The original face: two eyes and a mouth over the background
+pub struct FaceGenerator;
+
+/// The face over a striped lower half
+pub struct StripesGenerator;
+
+/// A face with a single eye in the middle
+pub struct CyclopsGenerator;
+
+/// The face drawn for the tokens minted with art version 1 before generators were registered for them.
+/// Its drawing is frozen: it ignores the art configuration and the options, so their art stays the same
+/// whatever generators and parameters are added later (tests/golden.rs checks it against the original renders).
+pub struct LegacyGenerator;
+
+/// Generators built into the contract, indexed by implementation id
+pub static BUILTIN_GENERATORS: [&(dyn ArtGenerator + Sync); 3] =
+    [&FaceGenerator, &StripesGenerator, &CyclopsGenerator];
+
+/// Picks the traits with `rng` (stream 0 of the seed), inherits them from the parents and applies the options
+fn pick_traits(
+    rng: &mut ArtRng,
+    seed: &Seed,
+    weights: &RarityWeights,
+    options: &ArtOptions,
+    parents: Option<&[Traits; 2]>,
+) -> Traits {
+    let mut traits = Traits::generate(rng, weights);
+    // Inheritance uses its own stream, so the rest of stream 0 doesn't change
+    if let Some(parents) = parents {
+        traits = inherit_traits(&mut seed.rng(INHERITANCE_STREAM), traits, parents);
+    }
+    // The chosen traits are applied after picking them all, so the rest of the random stream doesn't change
+    options.apply(&mut traits);
+    traits
+}
+
+/// Picks the traits and the foreground color, and draws the background
+fn start_image(
+    seed: &Seed,
+    config: &ArtConfig,
+    options: &ArtOptions,
+    parents: Option<&[Traits; 2]>,
+) -> (Image<32, 32>, Traits, Color) {
+    let mut rng = seed.rng(0);
+    let traits = pick_traits(&mut rng, seed, &config.rarity_weights, options, parents);
+
+    let random_color = Color {
+        red: rng.u8(),
+        green: rng.u8(),
+        blue: rng.u8(),
+    };
+    let fg_color = match traits.palette {
+        0 => random_color,
+        palette => {
+            let colors = &config.palettes[palette as usize - 1];
+            if colors.is_empty() {
+                random_color
+            } else {
+                colors[rng.below(colors.len() as u32) as usize]
+            }
+        }
+    };
+
+    let mut image = Image::new(config.background);
+    match traits.background {
+        0 => image.draw_gradient(config.gradient_start, config.gradient_end),
+        1 => {}
+        _ => image.draw_gradient(config.gradient_end, config.gradient_start),
+    }
+    (image, traits, fg_color)
+}
+
+/// Draws the eye centered at `eye`
+fn draw_eye(image: &mut Image<32, 32>, eye: Cell, traits: &Traits, color: Color) {
+    match traits.eyes {
+        0 => image.draw_line(eye.offset(0, -1), eye.offset(0, 1), color),
+        1 => image.draw_line(eye, eye, color),
+        _ => image.draw_line(eye.offset(-1, 0), eye.offset(1, 0), color),
+    }
+}
+
+/// Draws the mouth centered at `mouth`
+fn draw_mouth(image: &mut Image<32, 32>, mouth: Cell, traits: &Traits, color: Color) {
+    match traits.mouth {
+        0 => image.draw_ellipse(mouth, 3, 3, [false, false, true, true], color),
+        1 => image.draw_line(mouth.offset(-3, 1), mouth.offset(3, 1), color),
+        _ => image.draw_ellipse(mouth.offset(0, 3), 3, 3, [true, true, false, false], color),
+    }
+}
+
+impl ArtGenerator for FaceGenerator {
+    fn name(&self) -> &'static str {
+        "Face"
+    }
+
+    fn generate(
+        &self,
+        seed: &Seed,
+        config: &ArtConfig,
+        options: &ArtOptions,
+        parents: Option<&[Traits; 2]>,
+    ) -> (Image<32, 32>, Traits) {
+        let (mut image, traits, fg_color) = start_image(seed, config, options, parents);
+        draw_eye(&mut image, config.left_eye, &traits, fg_color);
+        draw_eye(&mut image, config.right_eye, &traits, fg_color);
+        draw_mouth(&mut image, config.mouth, &traits, fg_color);
+        (image, traits)
+    }
+}
+
+impl ArtGenerator for LegacyGenerator {
+    fn name(&self) -> &'static str {
+        "Face"
+    }
+
+    fn traits(&self, _seed: &Seed, _weights: &RarityWeights, _options: &ArtOptions, _parents: Option<&[Traits; 2]>) -> Traits {
+        // Gradient background, vertical eyes, smile and random color
+        Traits::default()
+    }
+
+    fn draws_overlays(&self) -> bool {
+        // The art of version 1 is frozen, so nothing is drawn over it
+        false
+    }
+
+    fn generate(
+        &self,
+        seed: &Seed,
+        _config: &ArtConfig,
+        _options: &ArtOptions,
+        _parents: Option<&[Traits; 2]>,
+    ) -> (Image<32, 32>, Traits) {
+        let mut rng = seed.rng(0);
+        let fg_color = Color {
+            red: rng.u8(),
+            green: rng.u8(),
+            blue: rng.u8(),
+        };
+
+        let mut image = Image::new(Color::from_hex(0xe3066e));
+        image.draw_gradient(Color::from_hex(0xff0000), Color::from_hex(0x0000ff));
+        image.draw_line(Cell::new(4, 4), Cell::new(4, 6), fg_color);
+        image.draw_line(Cell::new(10, 4), Cell::new(10, 6), fg_color);
+        image.draw_ellipse(Cell::new(7, 9), 3, 3, [false, false, true, true], fg_color);
+        (image, Traits::default())
+    }
+}
+
+impl ArtGenerator for StripesGenerator {
+    fn name(&self) -> &'static str {
+        "Stripes"
+    }
+
+    fn generate(
+        &self,
+        seed: &Seed,
+        config: &ArtConfig,
+        options: &ArtOptions,
+        parents: Option<&[Traits; 2]>,
+    ) -> (Image<32, 32>, Traits) {
+        let (mut image, traits) = FaceGenerator.generate(seed, config, options, parents);
+        let mut rng = seed.rng(1);
+        let stripe_color = Color {
+            red: rng.u8(),
+            green: rng.u8(),
+            blue: rng.u8(),
+        };
+        for y in (16..32).step_by(4) {
+            image.draw_line(Cell::new(0, y), Cell::new(31, y), stripe_color);
+        }
+        (image, traits)
+    }
+}
+
+impl ArtGenerator for CyclopsGenerator {
+    fn name(&self) -> &'static str {
+        "Cyclops"
+    }
+
+    fn generate(
+        &self,
+        seed: &Seed,
+        config: &ArtConfig,
+        options: &ArtOptions,
+        parents: Option<&[Traits; 2]>,
+    ) -> (Image<32, 32>, Traits) {
+        let (mut image, traits, fg_color) = start_image(seed, config, options, parents);
+        let eye = Cell::new(
+            (config.left_eye.x() + config.right_eye.x()) / 2,
+            (config.left_eye.y() + config.right_eye.y()) / 2,
+        );
+        let (a, b) = match traits.eyes {
+            0 => (1, 2),
+            1 => (1, 1),
+            _ => (2, 1),
+        };
+        image.draw_ellipse(eye, a, b, [true; 4], fg_color);
+        draw_mouth(&mut image, config.mouth, &traits, fg_color);
+        (image, traits)
+    }
+}
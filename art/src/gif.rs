@@ -0,0 +1,174 @@
+//! Minimal GIF encoder, so the art can also be served as a GIF image (`gif` feature).
+use alloc::vec::Vec;
+use crate::art::{Color, Image};
+
+/// Maximum number of colors of a GIF image
+const MAX_COLORS: usize = 256;
+
+/// Number of bits of the palette indexes
+const MIN_CODE_SIZE: u8 = 8;
+
+/// Code that resets the LZW table
+const CLEAR_CODE: u16 = 1 << MIN_CODE_SIZE;
+
+/// Code that ends the image data
+const END_CODE: u16 = CLEAR_CODE + 1;
+
+/// Number of palette indexes written between clear codes. The decoder adds a table entry for each code after
+/// the first one, so clearing the table before it reaches 512 entries keeps all the codes 9 bits wide.
+const CODES_PER_CLEAR: usize = 254;
+
+/// Writes LZW codes as a stream of bits, least significant bits first
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Returns the index of the color of `palette` closest to `color`
+fn closest_color(palette: &[Color], color: Color) -> u8 {
+    let distance = |other: &Color| {
+        let delta = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        delta(color.red, other.red) + delta(color.green, other.green) + delta(color.blue, other.blue)
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, other)| distance(other))
+        .map_or(0, |(index, _)| index as u8)
+}
+
+impl<const R: usize, const C: usize> Image<R, C> {
+    /// Returns the bytes of the GIF formatted image. Images with more than 256 colors
+    /// draw the extra ones with the closest of the first 256.
+    pub fn make_gif(&self) -> Vec<u8> {
+        let mut palette: Vec<Color> = Vec::with_capacity(MAX_COLORS);
+        let mut indexes = Vec::with_capacity(R * C);
+        for &pixel in self.pixels.iter().flatten() {
+            let index = match palette.iter().position(|&color| color == pixel) {
+                Some(index) => index as u8,
+                None if palette.len() < MAX_COLORS => {
+                    palette.push(pixel);
+                    (palette.len() - 1) as u8
+                }
+                None => closest_color(&palette, pixel),
+            };
+            indexes.push(index);
+        }
+
+        let mut out = Vec::new();
+        out.extend(b"GIF89a");
+        // Logical screen descriptor, with a global color table of 256 colors
+        out.extend((C as u16).to_le_bytes());
+        out.extend((R as u16).to_le_bytes());
+        out.extend([0xf7, 0, 0]);
+        for index in 0..MAX_COLORS {
+            let color = palette.get(index).copied().unwrap_or_default();
+            out.extend([color.red, color.green, color.blue]);
+        }
+        // Image descriptor, covering the whole screen
+        out.push(0x2c);
+        out.extend([0, 0, 0, 0]);
+        out.extend((C as u16).to_le_bytes());
+        out.extend((R as u16).to_le_bytes());
+        out.push(0);
+
+        // Image data: the palette indexes as literal LZW codes
+        let mut writer = BitWriter { bytes: Vec::new(), buffer: 0, bits: 0 };
+        let width = MIN_CODE_SIZE as u32 + 1;
+        for chunk in indexes.chunks(CODES_PER_CLEAR) {
+            writer.write(CLEAR_CODE, width);
+            for &index in chunk {
+                writer.write(index as u16, width);
+            }
+        }
+        writer.write(END_CODE, width);
+        out.push(MIN_CODE_SIZE);
+        for block in writer.finish().chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0);
+
+        out.push(0x3b); // Trailer
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes a GIF image with the reference decoder into its width, height and RGB bytes
+    fn decode_gif(bytes: &[u8]) -> (u16, u16, Vec<u8>) {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(bytes).unwrap();
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        let rgb = frame.buffer.chunks_exact(4).flat_map(|pixel| &pixel[..3]).copied().collect();
+        (frame.width, frame.height, rgb)
+    }
+
+    /// Returns an image whose pixels cycle through `colors` distinct colors
+    fn image_with_colors<const R: usize, const C: usize>(colors: usize) -> Image<R, C> {
+        let mut image = Image::new(Color::default());
+        for (index, pixel) in image.pixels.iter_mut().flatten().enumerate() {
+            *pixel = Color::from_hex((index % colors) * 0x010203);
+        }
+        image
+    }
+
+    fn assert_decodes_to_the_pixels<const R: usize, const C: usize>(image: Image<R, C>) {
+        assert_eq!(decode_gif(&image.make_gif()), (C as u16, R as u16, image.make_rgb()), "{R}x{C} image");
+    }
+
+    #[test]
+    fn gif_decodes_to_the_pixels() {
+        assert_decodes_to_the_pixels(image_with_colors::<32, 32>(256));
+        assert_decodes_to_the_pixels(image_with_colors::<32, 32>(3));
+        assert_decodes_to_the_pixels(Image::<32, 32>::new(Color::from_hex(0xe3066e)));
+    }
+
+    #[test]
+    fn gif_decodes_around_the_clear_codes() {
+        // Images ending just before, at and just after a clear code, and spanning several of them
+        assert_decodes_to_the_pixels(image_with_colors::<1, { CODES_PER_CLEAR - 1 }>(256));
+        assert_decodes_to_the_pixels(image_with_colors::<1, CODES_PER_CLEAR>(256));
+        assert_decodes_to_the_pixels(image_with_colors::<1, { CODES_PER_CLEAR + 1 }>(256));
+        assert_decodes_to_the_pixels(image_with_colors::<2, CODES_PER_CLEAR>(256));
+        assert_decodes_to_the_pixels(image_with_colors::<3, { CODES_PER_CLEAR + 1 }>(256));
+    }
+
+    #[test]
+    fn gif_draws_extra_colors_with_the_closest_one() {
+        // The first 256 pixels fill the palette, and each of the others is 1 away from one of them
+        let mut image = Image::<32, 32>::new(Color::default());
+        for (index, pixel) in image.pixels.iter_mut().flatten().enumerate() {
+            let green = (index % 256) as u8;
+            *pixel = Color { red: (index / 256) as u8 % 2, green, blue: 0 };
+        }
+        let (_, _, rgb) = decode_gif(&image.make_gif());
+        for (index, pixel) in rgb.chunks_exact(3).enumerate() {
+            assert_eq!(pixel, [0, (index % 256) as u8, 0], "pixel {index}");
+        }
+    }
+}
@@ -1,63 +1,315 @@
+// The exported ABI of the many public methods needs a deeper recursion than the default limit
+#![recursion_limit = "256"]
+
 extern crate alloc;
 
 // Modules and imports
-mod utils;
-mod art;
+pub mod utils;
+pub mod access;
+pub mod art;
+pub mod attributes;
+pub mod breeding;
+pub mod canvas;
+pub mod collections;
+pub mod config;
+pub mod formats;
+pub mod gallery;
+pub mod generators;
+#[cfg(feature = "gif")]
+pub mod gif;
+pub mod metadata;
+pub mod options;
+pub mod placeholder;
+pub mod rng;
+pub mod stages;
+pub mod tiers;
 
 use stylus_sdk::{
     prelude::*,
-    call::Call,
-    storage::StorageAddress,
-    alloy_primitives::{Address, U256}
+    abi::Bytes,
+    block,
+    call,
+    contract,
+    function_selector,
+    crypto,
+    evm,
+    msg,
+    storage::{
+        StorageAddress, StorageArray, StorageB256, StorageBool, StorageBytes, StorageMap, StorageU256, StorageU32,
+        StorageU64, StorageU8, StorageVec,
+    },
+    alloy_primitives::{Address, B256, U256, U32, U64, U8}
 };
+use alloc::{collections::BTreeMap, string::ToString};
 use alloy_sol_types::sol;
 use base64::Engine;
-use fastrand::Rng;
+use stylus_workshop_rust_calls::{call_with_gas_limit, ExternalCallFailed};
+use crate::access::{is_valid_role, DESIGNATED_ADMIN, ROLE_ART_MANAGER, ROLE_COLLECTION_MANAGER};
 use crate::art::{Color, Image, Cell};
+use crate::attributes::{RarityWeights, Traits, RARITY_WEIGHT_COUNT};
+use crate::breeding::{child_seed, decode_parent_traits, encode_parent_traits};
+use crate::collections::{collection_token_seed, CollectionStyle};
+use crate::canvas::{is_valid_canvas_pixel, PixelCanvas, CANVAS_COLORS, DEFAULT_CANVAS_PALETTE, DEFAULT_PAINT_COOLDOWN};
+use crate::config::{
+    is_valid_eye_position, is_valid_mouth_position, ArtConfig, CONFIGURABLE_PALETTES,
+    MAX_PALETTE_COLORS,
+};
+use crate::formats::{ArtFormat, SUPPORTED_FORMATS};
+use crate::gallery::{make_contact_sheet, ContactSheetOptions};
+use crate::generators::{ArtGenerator, LegacyGenerator, BUILTIN_GENERATORS};
+use crate::metadata::{make_metadata_json, make_metadata_uri};
+use crate::options::ArtOptions;
+use crate::placeholder::{make_placeholder, PLACEHOLDER_DESCRIPTION};
+use crate::rng::Seed;
+use crate::stages::{draw_stage, stage_for, DEFAULT_STAGE_THRESHOLDS, MAX_STAGE, STAGES};
+use crate::tiers::{default_tier_thresholds, draw_tier, tier_for, DEFAULT_ERC20_DECIMALS, MAX_TIER, TIERS};
 use crate::utils::FnvHasher;
 
-/// Generates the image for a given NFT token ID
-pub fn gen_art(address: Address, token_id: U256) -> Image<32, 32> {
+/// Name prefix of the tokens in the metadata document
+const METADATA_NAME_PREFIX: &str = "StylusNFT #";
+
+/// Description of the tokens in the metadata document
+const METADATA_DESCRIPTION: &str = "Generative art rendered on-chain by a Stylus contract";
+
+/// Art version used for the tokens minted from now on
+pub const LATEST_ART_VERSION: u8 = 3;
+
+/// Art version of the tokens that were not registered at mint time
+pub const LEGACY_ART_VERSION: u8 = 1;
+
+/// Generator id reported for the tokens that were not registered at mint time, drawn by the legacy generator.
+/// It can't be registered.
+pub const LEGACY_GENERATOR_ID: u8 = u8::MAX;
+
+/// Maximum number of tokens in a collection preview
+pub const MAX_PREVIEW_TOKENS: usize = 16;
+
+/// Maximum number of tokens drawn in the portrait of an owner
+pub const MAX_PORTRAIT_TOKENS: usize = 16;
+
+/// Maximum number of tokens whose art can be generated in a single batch call
+pub const MAX_BATCH_SIZE: usize = 20;
+
+/// Derives the seed for a given NFT token ID and owner, as done by art version 1.
+/// Note that anyone can compute it in advance, since it only depends on public inputs.
+pub fn legacy_token_seed(address: Address, token_id: U256) -> u64 {
     let mut hasher = FnvHasher::new();
     hasher.update(token_id.as_le_slice());
     hasher.update(address.as_slice());
-    let mut rng = Rng::with_seed(hasher.output());
-
-    let bg_color = Color::from_hex(0xe3066e);
-    let fg_color = Color {
-        red: rng.u8(..),
-        green: rng.u8(..),
-        blue: rng.u8(..),
-    };
-
-    let mut image = Image::new(bg_color);
-
-    image.draw_gradient(Color::from_hex(0xff0000), Color::from_hex(0x0000ff));
-    image.draw_line(Cell::new(4, 4), Cell::new(4, 6), fg_color);
-    image.draw_line(Cell::new(10, 4), Cell::new(10, 6), fg_color);
-    image.draw_ellipse(Cell::new(7, 9), 3, 3, [false, false, true, true], fg_color);
-    image
+    hasher.output()
+}
+
+/// Derives the seed used by the generators from the keccak-based seed revealed for a token
+pub fn revealed_token_seed(version: u8, seed: B256) -> Seed {
+    match version {
+        // Version 2: the first 8 bytes seed fastrand's wyrand
+        2 => Seed::Legacy(u64::from_le_bytes(seed[..8].try_into().unwrap())),
+        // Version 3: the whole seed seeds xoshiro256**
+        _ => Seed::Portable(seed.0),
+    }
+}
+
+/// Hashes a token id, to make deterministic choices for it
+fn token_id_hash(token_id: U256) -> u64 {
+    let mut hasher = FnvHasher::new();
+    hasher.update(token_id.as_le_slice());
+    hasher.output()
+}
+
+/// Generates the image and the traits for a seed using `generator`, inheriting the traits of the `parents`
+/// of bred tokens and honouring the `options` chosen by the owner,
+/// with the decorations unlocked up to `stage` and the ring of the ERC-20 balance `tier`
+pub fn gen_art(
+    seed: &Seed,
+    generator: &dyn ArtGenerator,
+    config: &ArtConfig,
+    options: &ArtOptions,
+    parents: Option<&[Traits; 2]>,
+    stage: u8,
+    tier: u8,
+) -> (Image<32, 32>, Traits) {
+    let (mut image, traits) = generator.generate(seed, config, options, parents);
+    if generator.draws_overlays() {
+        options.draw_accessories(&mut image, config);
+        draw_tier(&mut image, tier);
+        draw_stage(&mut image, stage, config);
+    }
+    (image, traits)
+}
+
+/// Returns the `data:` URI of the PNG encoded image
+pub fn make_image_uri(image: &Image<32, 32>) -> String {
+    make_png_uri(&image.make_png())
+}
+
+/// Returns the `data:` URI of an image already encoded as PNG
+pub fn make_png_uri(image_png: &[u8]) -> String {
+    let mut image_str = String::from("data:image/png;base64,");
+    base64::engine::general_purpose::STANDARD.encode_string(image_png, &mut image_str);
+    image_str
 }
 
 // Solidity interface for the NFT contract
 sol_interface! {
     interface Nft {
         function ownerOf(uint256 token_id) external returns(address);
+        function artOwnerOf(uint256 token_id) external view returns(address);
+        function balanceOf(address owner) external view returns(uint256);
+        function tokenOfOwnerByIndex(address owner, uint256 index) external view returns(uint256);
+        function artContractOf(uint256 token_id) external view returns(address);
+    }
+
+    interface IERC721 {
+        function ownerOf(uint256 token_id) external view returns(address);
+    }
+
+    interface Erc20 {
+        function balanceOf(address owner) external view returns(uint256);
+        function decimals() external view returns(uint8);
     }
 }
 
+/// Weights of the backgrounds, eyes, mouths and palettes, as returned by `getRarityWeights`
+type RarityWeightLists = (Vec<u32>, Vec<u32>, Vec<u32>, Vec<u32>);
+
 /// Entrypoint for Art contract
 #[storage]
 #[entrypoint]
 pub struct StylusNFTArt {
     /// The NFT contract that this Art contract delivers the art for
     token_contract_address: StorageAddress,
+    /// Account allowed to configure the art generator
+    admin: StorageAddress,
+    /// Color of the "Solid" background
+    background_color: StorageU32,
+    /// First and last stops of the gradient backgrounds
+    gradient_stops: StorageArray<StorageU32, 2>,
+    /// Colors of the configurable palettes
+    palettes: StorageArray<StorageVec<StorageU32>, CONFIGURABLE_PALETTES>,
+    /// Centers (x, y) of the left eye, right eye and mouth
+    feature_positions: StorageArray<StorageU8, 6>,
+    /// Relative weights used to pick the backgrounds, eyes, mouths and palettes, one after the other
+    rarity_weights: StorageArray<StorageU32, RARITY_WEIGHT_COUNT>,
+    /// Generator id to built-in implementation id + 1 (0 if not registered)
+    generators: StorageMap<u8, StorageU8>,
+    /// Ids of the registered generators
+    generator_ids: StorageVec<StorageU8>,
+    /// Token id to the generator id + 1 chosen by its owner or assigned at mint time (0 if randomly assigned)
+    token_generators: StorageMap<U256, StorageU8>,
+    /// Token id to the art version it was minted with (0 if not registered)
+    token_versions: StorageMap<U256, StorageU8>,
+    /// Token id to the keccak-based seed revealed by the NFT contract (zero if not revealed)
+    token_seeds: StorageMap<U256, StorageB256>,
+    /// Token id to the timestamp since which it's held by its current owner (0 if unknown)
+    token_held_since: StorageMap<U256, StorageU64>,
+    /// Number of seconds a token must be held to reach each stage after the first one
+    stage_thresholds: StorageVec<StorageU64>,
+    /// Whether the generated images are cached
+    cache_enabled: StorageBool,
+    /// Incremented whenever the art configuration changes, invalidating all the cached images
+    cache_epoch: StorageU32,
+    /// Token id to its cached PNG image (empty if not cached)
+    cached_images: StorageMap<U256, StorageBytes>,
+    /// Token id to the owner its cached image was rendered for
+    cached_owners: StorageMap<U256, StorageAddress>,
+    /// Token id to the cache epoch its cached image was rendered in
+    cached_epochs: StorageMap<U256, StorageU32>,
+    /// Token id to the stage its cached image was rendered for
+    cached_stages: StorageMap<U256, StorageU8>,
+    /// Token id to the ERC-20 balance tier its cached image was rendered for
+    cached_tiers: StorageMap<U256, StorageU8>,
+    /// The ERC-20 contract whose balance of the owner selects the tier of the art
+    erc20_token_contract_address: StorageAddress,
+    /// Minimum ERC-20 balance needed to reach each tier after the first one
+    balance_tiers: StorageVec<StorageU256>,
+    /// Background and foreground colors of the placeholder of unrevealed tokens
+    placeholder_colors: StorageArray<StorageU32, 2>,
+    /// Token id to the encoded options chosen by its owner (empty if none)
+    token_options: StorageMap<U256, StorageBytes>,
+    /// Collaborative pixel canvas painted by the token holders
+    #[borrow]
+    canvas: PixelCanvas,
+    /// Token id to the ids of its parents (only set for bred tokens)
+    token_parents: StorageMap<U256, StorageArray<StorageU256, 2>>,
+    /// Token id to the traits of its parents, one byte per trait (empty if it was not bred)
+    token_parent_traits: StorageMap<U256, StorageBytes>,
+    /// ERC-721 contract to its registration and style, for the collections registered besides the NFT contract
+    collections: StorageMap<Address, CollectionStyle>,
+    /// Addresses of the collections that have ever been registered
+    collection_addresses: StorageVec<StorageAddress>,
+    /// Account the admin is being transferred to, until it accepts it (zero if none)
+    pending_admin: StorageAddress,
+    /// Account to its roles, as bit flags (the admin implicitly has all of them)
+    roles: StorageMap<Address, StorageU8>,
+    /// Whether the revert data of failed calls to other contracts is re-thrown as is, instead of in ExternalCallFailed
+    forward_call_reverts: StorageBool,
+    /// Gas given to each call to the NFT contract (0 for all the gas left)
+    nft_call_gas_limit: StorageU64,
+    /// Gas given to each call to the ERC-20 contract (0 for all the gas left)
+    erc20_call_gas_limit: StorageU64,
+    /// Gas given to each call to a registered collection (0 for all the gas left)
+    collection_call_gas_limit: StorageU64,
+    /// Token id to the account holding it, recorded on transfers (zero while its minter holds it)
+    token_holders: StorageMap<U256, StorageAddress>,
 }
 
-// Declares Solidity error types
+// Declares Solidity events and error types
 sol! {
+    event BackgroundColorUpdated(uint32 color);
+    event GradientUpdated(uint32 start, uint32 end);
+    event PaletteUpdated(uint8 indexed palette, uint32[] colors);
+    event FeaturePositionsUpdated(uint8 left_eye_x, uint8 left_eye_y, uint8 right_eye_x, uint8 right_eye_y, uint8 mouth_x, uint8 mouth_y);
+    event GeneratorRegistered(uint8 indexed generator_id, uint8 implementation);
+    event TokenGeneratorChosen(uint256 indexed token_id, uint8 generator_id);
+    event TokenRegistered(uint256 indexed token_id, uint8 version, uint8 generator_id);
+    event TokenSeedSet(uint256 indexed token_id, bytes32 seed);
+    event CacheEnabledUpdated(bool enabled);
+    event StageThresholdsUpdated(uint64[] thresholds);
+    event BalanceTiersUpdated(uint256[] thresholds);
+    event RarityWeightsUpdated(uint32[] background, uint32[] eyes, uint32[] mouth, uint32[] palette);
+    event PlaceholderUpdated(uint32 background, uint32 foreground);
+    event TokenOptionsSet(uint256 indexed token_id, bytes options);
+    event CacheInvalidated(uint256 indexed token_id);
+    event PixelPainted(uint256 indexed token_id, address indexed painter, uint8 x, uint8 y, uint8 color);
+    event CanvasPaletteUpdated(uint32[] colors);
+    event PaintCooldownUpdated(uint64 cooldown);
+    event TokenBred(uint256 indexed token_id, uint256 parent_a, uint256 parent_b);
+    event CollectionRegistered(address indexed collection, string name);
+    event CollectionUnregistered(address indexed collection);
+    event CollectionGeneratorChosen(address indexed collection, uint8 generator_id);
+    event CollectionColorsUpdated(address indexed collection, bool custom, uint32 background, uint32 gradient_start, uint32 gradient_end);
+    event AdminTransferStarted(address indexed previous_admin, address indexed new_admin);
+    event AdminTransferred(address indexed previous_admin, address indexed new_admin);
+    event RoleGranted(uint8 indexed role, address indexed account);
+    event RoleRevoked(uint8 indexed role, address indexed account);
+    event TokenContractAddressUpdated(address token_contract_address);
+    event Erc20TokenContractAddressUpdated(address erc20_token_contract_address);
+    event CallRevertForwardingUpdated(bool forward);
+    event CallGasLimitsUpdated(uint64 nft, uint64 erc20, uint64 collection);
+
     error AlreadyInitialized();
-    error ExternalCallFailed();
+    error NotAuthorized(address account);
+    error InvalidPalette(uint8 palette, uint256 colors);
+    error InvalidFeaturePosition(uint8 x, uint8 y);
+    error InvalidRarityWeights();
+    error UnknownGenerator(uint8 generator_id);
+    error GeneratorAlreadyRegistered(uint8 generator_id);
+    error AlreadyRegistered(uint256 token_id);
+    error NotRevealed(uint256 token_id);
+    error AlreadyRevealed(uint256 token_id);
+    error TooManyTokens(uint256 count, uint256 max);
+    error LengthMismatch(uint256 token_ids, uint256 owners);
+    error InvalidStageThresholds();
+    error InvalidBalanceTiers();
+    error InvalidArtOptions();
+    error InvalidCanvasPixel(uint8 x, uint8 y);
+    error InvalidCanvasColor(uint8 color);
+    error InvalidCanvasPalette(uint256 colors);
+    error PaintCooldown(uint256 token_id, uint64 next_paint_time);
+    error UnknownCollection(address collection);
+    error InvalidRole(uint8 role);
+    error UnsupportedFormat(uint8 format);
+    error CacheDisabled();
 }
 
 /// Error definitions
@@ -67,46 +319,1396 @@ pub enum StylusNftArtError {
     AlreadyInitialized(AlreadyInitialized),
     /// A call to an external contract failed
     ExternalCallFailed(ExternalCallFailed),
+    /// The sender is not allowed to perform this action
+    NotAuthorized(NotAuthorized),
+    /// The palette does not exist or has an invalid number of colors
+    InvalidPalette(InvalidPalette),
+    /// A feature can't be drawn at that position
+    InvalidFeaturePosition(InvalidFeaturePosition),
+    /// A trait doesn't have one weight per value, its weights are all zero or their sum overflows
+    InvalidRarityWeights(InvalidRarityWeights),
+    /// The generator (or its implementation) does not exist
+    UnknownGenerator(UnknownGenerator),
+    /// The generator id is already registered, and its implementation can't be changed
+    GeneratorAlreadyRegistered(GeneratorAlreadyRegistered),
+    /// The token has already been registered
+    AlreadyRegistered(AlreadyRegistered),
+    /// The seed of the token has not been revealed yet
+    NotRevealed(NotRevealed),
+    /// The seed of the token has already been revealed
+    AlreadyRevealed(AlreadyRevealed),
+    /// Too many tokens were requested in a single call
+    TooManyTokens(TooManyTokens),
+    /// There must be one owner per token id
+    LengthMismatch(LengthMismatch),
+    /// Stage thresholds must be increasing, and there can't be more than stages
+    InvalidStageThresholds(InvalidStageThresholds),
+    /// Balance tiers must be increasing, and there can't be more than tiers
+    InvalidBalanceTiers(InvalidBalanceTiers),
+    /// The art options can't be decoded, choose traits that don't exist, or customise frozen art
+    InvalidArtOptions(InvalidArtOptions),
+    /// The pixel is outside the canvas
+    InvalidCanvasPixel(InvalidCanvasPixel),
+    /// The color is not in the palette of the canvas
+    InvalidCanvasColor(InvalidCanvasColor),
+    /// The palette of the canvas must have exactly 16 colors
+    InvalidCanvasPalette(InvalidCanvasPalette),
+    /// The token has painted a pixel too recently
+    PaintCooldown(PaintCooldown),
+    /// The ERC-721 collection has not been registered
+    UnknownCollection(UnknownCollection),
+    /// The role does not exist
+    InvalidRole(InvalidRole),
+    /// The format does not exist, or the contract was built without it
+    UnsupportedFormat(UnsupportedFormat),
+    /// The cache of generated images is disabled
+    CacheDisabled(CacheDisabled),
 }
 
-// Contract implementation
-#[public]
+// Helper, private functions
 impl StylusNFTArt {
-    /// Generates the art of a specific token_id
-    #[selector(name = "generateArt")]
-    pub fn generate_art(&mut self, token_id: U256) -> Result<String, StylusNftArtError> {
-        // Call the NFT contract to get the owner
+    /// Check that the sender is the admin of the contract
+    fn only_admin(&self) -> Result<(), StylusNftArtError> {
+        let sender = msg::sender();
+        if sender != self.admin.get() {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: sender }));
+        }
+        Ok(())
+    }
+
+    /// Check that the sender is the admin of the contract, or has been granted `role`
+    fn only_role(&self, role: u8) -> Result<(), StylusNftArtError> {
+        let sender = msg::sender();
+        if sender != self.admin.get() && self.roles.get(sender).to::<u8>() & role == 0 {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: sender }));
+        }
+        Ok(())
+    }
+
+    /// Returns the revert data of a failed call to the function `selector` of `target`: an `ExternalCallFailed` error
+    /// carrying the revert data of `target`, or that revert data itself if the admin chose to forward it
+    fn call_failed(&self, target: Address, selector: [u8; 4], error: call::Error) -> Vec<u8> {
+        stylus_workshop_rust_calls::call_failed(target, selector, error, self.forward_call_reverts.get())
+    }
+
+    /// Calls the NFT contract to get the owner of a token
+    fn token_owner(&mut self, token_id: U256) -> Result<Address, Vec<u8>> {
+        let token_contract_address = self.token_contract_address.get();
+        let token_contract = Nft::new(token_contract_address);
+        let config = call_with_gas_limit(self.nft_call_gas_limit.get().to::<u64>());
+        token_contract
+            .owner_of(config, token_id)
+            .map_err(|e| self.call_failed(token_contract_address, function_selector!("ownerOf", U256), e))
+    }
+
+    /// Calls the NFT contract to get the account the art of a token is rendered for
+    /// (its minter or its current owner, depending on the NFT contract configuration)
+    fn art_owner(&self, token_id: U256) -> Result<Address, Vec<u8>> {
         let token_contract_address = self.token_contract_address.get();
         let token_contract = Nft::new(token_contract_address);
-        let config = Call::new();
-        let owner = token_contract
+        let config = call_with_gas_limit(self.nft_call_gas_limit.get().to::<u64>());
+        token_contract
+            .art_owner_of(config, token_id)
+            .map_err(|e| self.call_failed(token_contract_address, function_selector!("artOwnerOf", U256), e))
+    }
+
+    /// Reads the parameters of the art generator from storage
+    fn art_config(&self) -> ArtConfig {
+        let color = |value: U32| Color::from_hex(value.to::<usize>());
+        let cell = |index: usize| Cell::new(
+            self.feature_positions.get(index).unwrap_or_default().to::<usize>(),
+            self.feature_positions.get(index + 1).unwrap_or_default().to::<usize>(),
+        );
+        let palette = |index: usize| {
+            let colors = self.palettes.getter(index).unwrap();
+            (0..colors.len()).map(|i| color(colors.get(i).unwrap_or_default())).collect()
+        };
+        ArtConfig {
+            background: color(self.background_color.get()),
+            gradient_start: color(self.gradient_stops.get(0).unwrap_or_default()),
+            gradient_end: color(self.gradient_stops.get(1).unwrap_or_default()),
+            palettes: [palette(0), palette(1), palette(2)],
+            left_eye: cell(0),
+            right_eye: cell(2),
+            mouth: cell(4),
+            placeholder_background: color(self.placeholder_colors.get(0).unwrap_or_default()),
+            placeholder_foreground: color(self.placeholder_colors.get(1).unwrap_or_default()),
+            rarity_weights: self.rarity_weights(),
+        }
+    }
+
+    /// Reads the rarity weights from storage
+    fn rarity_weights(&self) -> RarityWeights {
+        let weights = core::array::from_fn(|index| self.rarity_weights.get(index).unwrap_or_default().to::<u32>());
+        RarityWeights::from_slice(&weights)
+    }
+
+    /// Picks one of the first `count` registered generators from the token id,
+    /// so registering more generators doesn't change the pick
+    fn random_generator_id(&self, token_id: U256, count: usize) -> u8 {
+        let count = count.min(self.generator_ids.len());
+        if count == 0 {
+            return 0;
+        }
+        let index = token_id_hash(token_id) % count as u64;
+        self.generator_ids.get(index as usize).unwrap_or_default().to::<u8>()
+    }
+
+    /// Returns the generator used by a token: the one chosen by its owner or assigned at mint time,
+    /// or the legacy generator for the tokens that were not registered at mint time
+    fn token_generator(&self, token_id: U256) -> (u8, &'static dyn ArtGenerator) {
+        let generator_id = match self.token_generators.get(token_id).to::<u8>() {
+            0 => LEGACY_GENERATOR_ID,
+            chosen => chosen - 1,
+        };
+        (generator_id, self.generator(generator_id))
+    }
+
+    /// Returns the implementation of a generator (the first built-in one if it's not registered)
+    fn generator(&self, generator_id: u8) -> &'static dyn ArtGenerator {
+        if generator_id == LEGACY_GENERATOR_ID {
+            return &LegacyGenerator;
+        }
+        let implementation = self.generators.get(generator_id).to::<usize>();
+        match BUILTIN_GENERATORS.get(implementation.wrapping_sub(1)) {
+            Some(&generator) => generator,
+            None => BUILTIN_GENERATORS[0],
+        }
+    }
+
+    /// Returns the art configuration of a registered collection: the one of the Art contract,
+    /// with the colors of the collection if it has its own
+    fn collection_art_config(&self, collection: Address) -> ArtConfig {
+        let mut config = self.art_config();
+        let style = self.collections.getter(collection);
+        if style.custom_colors.get() {
+            let color = |index: usize| Color::from_hex(style.colors.get(index).unwrap_or_default().to::<usize>());
+            config.background = color(0);
+            config.gradient_start = color(1);
+            config.gradient_end = color(2);
+        }
+        config
+    }
+
+    /// Calls a registered ERC-721 collection to get the owner of one of its tokens,
+    /// and returns the seed and the generator of its art
+    fn collection_token_art(
+        &self,
+        collection: Address,
+        token_id: U256,
+    ) -> Result<(Seed, &'static dyn ArtGenerator), Vec<u8>> {
+        let style = self.collections.getter(collection);
+        if !style.registered.get() {
+            return Err(StylusNftArtError::UnknownCollection(UnknownCollection { collection }).into());
+        }
+        let generator_id = match style.generator.get().to::<u8>() {
+            0 => self.random_generator_id(token_id, style.generator_count.get().to::<usize>()),
+            chosen => chosen - 1,
+        };
+        let config = call_with_gas_limit(self.collection_call_gas_limit.get().to::<u64>());
+        let owner = IERC721::new(collection)
             .owner_of(config, token_id)
-            .map_err(|_e| StylusNftArtError::ExternalCallFailed(ExternalCallFailed {}))?;
+            .map_err(|e| self.call_failed(collection, function_selector!("ownerOf", U256), e))?;
+        Ok((collection_token_seed(collection, token_id, owner), self.generator(generator_id)))
+    }
+
+    /// Returns the art version a token was minted with
+    fn token_version(&self, token_id: U256) -> u8 {
+        match self.token_versions.get(token_id).to::<u8>() {
+            0 => LEGACY_ART_VERSION,
+            version => version,
+        }
+    }
+
+    /// Returns the seed used to generate the art of a token, according to its art version
+    fn token_seed(&self, token_id: U256, owner: Address) -> Result<Seed, StylusNftArtError> {
+        match self.token_version(token_id) {
+            1 => Ok(Seed::Legacy(legacy_token_seed(owner, token_id))),
+            version => {
+                let seed = self.token_seeds.get(token_id);
+                if seed.is_zero() {
+                    return Err(StylusNftArtError::NotRevealed(NotRevealed { token_id }));
+                }
+                Ok(revealed_token_seed(version, seed))
+            }
+        }
+    }
+
+    /// Checks that a batch call received one owner per token id, and at most `max` token ids
+    fn check_batch(token_ids: &[U256], owners: &[Address], max: usize) -> Result<(), StylusNftArtError> {
+        if token_ids.len() > max {
+            return Err(StylusNftArtError::TooManyTokens(TooManyTokens {
+                count: U256::from(token_ids.len()),
+                max: U256::from(max),
+            }));
+        }
+        if token_ids.len() != owners.len() {
+            return Err(StylusNftArtError::LengthMismatch(LengthMismatch {
+                token_ids: U256::from(token_ids.len()),
+                owners: U256::from(owners.len()),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Generates the PNG image of a token with the current configuration, its options and the traits of its parents
+    fn render_token(&self, token_id: U256, seed: &Seed, generator: &dyn ArtGenerator, stage: u8, tier: u8) -> Vec<u8> {
+        self.token_image(token_id, seed, generator, stage, tier).make_png()
+    }
+
+    /// Generates the image of a token with the current configuration, its options and the traits of its parents
+    fn token_image(
+        &self,
+        token_id: U256,
+        seed: &Seed,
+        generator: &dyn ArtGenerator,
+        stage: u8,
+        tier: u8,
+    ) -> Image<32, 32> {
+        let parents = self.token_parent_traits(token_id);
+        let options = self.token_options(token_id);
+        gen_art(seed, generator, &self.art_config(), &options, parents.as_ref(), stage, tier).0
+    }
+
+    /// Renders the art of tokens, given the owner of each one, into a contact sheet labeled with their ids,
+    /// as a PNG `data:` URI. Tokens whose seed has not been revealed yet are drawn as a blank tile.
+    fn contact_sheet_uri(&self, token_ids: &[U256], owners: &[Address]) -> Result<String, Vec<u8>> {
+        let config = self.art_config();
+        let mut tiers = BTreeMap::new();
+        let mut tiles = Vec::with_capacity(token_ids.len());
+        for (&token_id, &owner) in token_ids.iter().zip(owners) {
+            let image = match self.token_seed(token_id, owner) {
+                Ok(seed) => {
+                    let (_, generator) = self.token_generator(token_id);
+                    let (stage, tier) = self.token_overlays(token_id, owner, generator, &mut tiers)?;
+                    let options = self.token_options(token_id);
+                    let parents = self.token_parent_traits(token_id);
+                    gen_art(&seed, generator, &config, &options, parents.as_ref(), stage, tier).0
+                }
+                Err(StylusNftArtError::NotRevealed(_)) => Image::new(config.background),
+                Err(err) => return Err(err.into()),
+            };
+            tiles.push((token_id.to_string(), image));
+        }
+        let sheet = make_contact_sheet(&tiles, &ContactSheetOptions::default());
+        Ok(make_png_uri(&sheet.make_png(1)))
+    }
+
+    /// Returns the cached PNG image of a token, if it was rendered for the same owner, configuration, stage and tier
+    fn cached_png(&self, token_id: U256, owner: Address, stage: u8, tier: u8) -> Option<Vec<u8>> {
+        let cached = self.cache_enabled.get()
+            && self.cached_owners.get(token_id) == owner
+            && self.cached_epochs.get(token_id) == self.cache_epoch.get()
+            && self.cached_stages.get(token_id).to::<u8>() == stage
+            && self.cached_tiers.get(token_id).to::<u8>() == tier;
+        let image_png = self.cached_images.getter(token_id).get_bytes();
+        (cached && !image_png.is_empty()).then_some(image_png)
+    }
+
+    /// Returns the PNG image of a token, from the cache if it was rendered for the same owner and configuration.
+    /// It never writes to the cache, so that the art can be generated by views (see `warmCache`).
+    fn token_png(
+        &self,
+        token_id: U256,
+        owner: Address,
+        seed: &Seed,
+        generator: &dyn ArtGenerator,
+        stage: u8,
+        tier: u8,
+    ) -> Vec<u8> {
+        match self.cached_png(token_id, owner, stage, tier) {
+            Some(image_png) => image_png,
+            None => self.render_token(token_id, seed, generator, stage, tier),
+        }
+    }
+
+    /// Returns the stage reached by a token, according to how long its current owner has held it.
+    /// Tokens held since an unknown time stay at the first stage.
+    fn token_stage(&self, token_id: U256) -> u8 {
+        let held_since = self.token_held_since.get(token_id).to::<u64>();
+        if held_since == 0 {
+            return 0;
+        }
+        let held_for = block::timestamp().saturating_sub(held_since);
+        stage_for(held_for, &self.stage_thresholds())
+    }
 
+    /// Reads the stage thresholds from storage
+    fn stage_thresholds(&self) -> Vec<u64> {
+        (0..self.stage_thresholds.len())
+            .map(|i| self.stage_thresholds.get(i).unwrap_or_default().to::<u64>())
+            .collect()
+    }
+
+    /// Replaces the stage thresholds
+    fn set_thresholds(&mut self, thresholds: &[u64]) {
+        self.stage_thresholds.truncate(0);
+        for &threshold in thresholds {
+            self.stage_thresholds.push(U64::from(threshold));
+        }
+    }
+
+    /// Calls the ERC-20 contract to get the balance of `owner`, and returns the tier it reaches.
+    /// Without ERC-20 contract or tiers, every owner stays in the first tier.
+    fn owner_tier(&self, owner: Address) -> Result<u8, Vec<u8>> {
+        let thresholds = self.balance_tiers();
+        let erc20_token_contract_address = self.erc20_token_contract_address.get();
+        if thresholds.is_empty() || erc20_token_contract_address.is_zero() {
+            return Ok(0);
+        }
+        let erc20_token_contract = Erc20::new(erc20_token_contract_address);
+        let config = call_with_gas_limit(self.erc20_call_gas_limit.get().to::<u64>());
+        let balance = erc20_token_contract
+            .balance_of(config, owner)
+            .map_err(|e| self.call_failed(erc20_token_contract_address, function_selector!("balanceOf", Address), e))?;
+        Ok(tier_for(balance, &thresholds))
+    }
+
+    /// Returns the tier of the account currently holding a token (`owner` if no transfer recorded it), which
+    /// may differ from the one its art is rendered for. `tiers` keeps the tiers already looked up, so that
+    /// rendering many tokens of the same holder calls the ERC-20 contract once.
+    fn token_tier(&self, token_id: U256, owner: Address, tiers: &mut BTreeMap<Address, u8>) -> Result<u8, Vec<u8>> {
+        let holder = match self.token_holders.get(token_id) {
+            holder if holder.is_zero() => owner,
+            holder => holder,
+        };
+        if let Some(&tier) = tiers.get(&holder) {
+            return Ok(tier);
+        }
+        let tier = self.owner_tier(holder)?;
+        tiers.insert(holder, tier);
+        Ok(tier)
+    }
+
+    /// Returns the stage and the tier drawn over the art of a token, which are both 0 for the generators
+    /// that draw nothing over their images (the art of version 1), without looking up the ERC-20 balance
+    fn token_overlays(
+        &self,
+        token_id: U256,
+        owner: Address,
+        generator: &dyn ArtGenerator,
+        tiers: &mut BTreeMap<Address, u8>,
+    ) -> Result<(u8, u8), Vec<u8>> {
+        if !generator.draws_overlays() {
+            return Ok((0, 0));
+        }
+        Ok((self.token_stage(token_id), self.token_tier(token_id, owner, tiers)?))
+    }
+
+    /// Generates the art of a token for a specific address (assuming it's the owner), as a PNG `data:` URI
+    fn token_art_uri(&self, token_id: U256, owner: Address, tiers: &mut BTreeMap<Address, u8>) -> Result<String, Vec<u8>> {
+        let (_, generator) = self.token_generator(token_id);
+        let seed = self.token_seed(token_id, owner)?;
+        let (stage, tier) = self.token_overlays(token_id, owner, generator, tiers)?;
+        let image_png = self.token_png(token_id, owner, &seed, generator, stage, tier);
+        Ok(make_png_uri(&image_png))
+    }
+
+    /// Generates the metadata JSON document of a token for a specific address (assuming it's the owner), as a `data:` URI
+    fn token_metadata_uri(&self, token_id: U256, owner: Address, tiers: &mut BTreeMap<Address, u8>) -> Result<String, Vec<u8>> {
+        let (_, generator) = self.token_generator(token_id);
+        let seed = self.token_seed(token_id, owner)?;
+        let options = self.token_options(token_id);
+        let traits = generator.traits(&seed, &self.rarity_weights(), &options, self.token_parent_traits(token_id).as_ref());
+        let (stage, tier) = self.token_overlays(token_id, owner, generator, tiers)?;
+        let image_uri = make_png_uri(&self.token_png(token_id, owner, &seed, generator, stage, tier));
+        let name = METADATA_NAME_PREFIX.to_string() + &token_id.to_string();
+        let mut attributes = traits.attributes().to_vec();
+        attributes.push(("Style", generator.name()));
+        if generator.draws_overlays() {
+            attributes.push(("Stage", STAGES[stage as usize]));
+            attributes.push(("Tier", TIERS[tier as usize]));
+            attributes.extend(options.accessory_names().map(|name| ("Accessory", name)));
+        }
+        let json = make_metadata_json(&name, METADATA_DESCRIPTION, &image_uri, &attributes);
+        Ok(make_metadata_uri(&json))
+    }
+
+    /// Reads the balance tiers from storage
+    fn balance_tiers(&self) -> Vec<U256> {
+        (0..self.balance_tiers.len())
+            .map(|i| self.balance_tiers.get(i).unwrap_or_default())
+            .collect()
+    }
+
+    /// Replaces the balance tiers
+    fn set_tiers(&mut self, thresholds: &[U256]) {
+        self.balance_tiers.truncate(0);
+        for &threshold in thresholds {
+            self.balance_tiers.push(threshold);
+        }
+    }
+
+    /// Reads the options chosen by the owner of a token from storage
+    fn token_options(&self, token_id: U256) -> ArtOptions {
+        ArtOptions::decode(&self.token_options.getter(token_id).get_bytes()).unwrap_or_default()
+    }
+
+    /// Reads the ids of the parents of a bred token from storage (`None` if it was not bred)
+    fn token_parents(&self, token_id: U256) -> Option<[U256; 2]> {
+        if self.token_parent_traits.getter(token_id).is_empty() {
+            return None;
+        }
+        let parents = self.token_parents.getter(token_id);
+        Some([parents.get(0).unwrap_or_default(), parents.get(1).unwrap_or_default()])
+    }
+
+    /// Reads the traits of the parents of a bred token from storage (`None` if it was not bred)
+    fn token_parent_traits(&self, token_id: U256) -> Option<[Traits; 2]> {
+        decode_parent_traits(&self.token_parent_traits.getter(token_id).get_bytes())
+    }
+
+    /// Returns the traits of a token whose seed has been revealed, without the options chosen by its owner
+    fn revealed_traits(&self, token_id: U256) -> Result<Traits, StylusNftArtError> {
+        let seed = self.token_seeds.get(token_id);
+        if seed.is_zero() {
+            return Err(StylusNftArtError::NotRevealed(NotRevealed { token_id }));
+        }
+        let seed = revealed_token_seed(self.token_version(token_id), seed);
+        let (_, generator) = self.token_generator(token_id);
+        Ok(generator.traits(&seed, &self.rarity_weights(), &ArtOptions::default(), self.token_parent_traits(token_id).as_ref()))
+    }
+
+    /// Removes the cached image of a token
+    fn clear_cached_image(&mut self, token_id: U256) {
+        self.cached_images.setter(token_id).erase();
+        self.cached_owners.delete(token_id);
+        self.cached_epochs.delete(token_id);
+        self.cached_stages.delete(token_id);
+        self.cached_tiers.delete(token_id);
+    }
+
+    /// Invalidates all the cached images, after a change in the art configuration
+    fn bump_cache_epoch(&mut self) {
+        self.cache_epoch.set(self.cache_epoch.get() + U32::from(1));
+    }
+
+    /// Only the NFT contract can call this method
+    fn only_token_contract(&self) -> Result<(), StylusNftArtError> {
+        if msg::sender() != self.token_contract_address.get() {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: msg::sender() }));
+        }
+        Ok(())
+    }
+
+    /// Maps a new generator id to a built-in implementation
+    fn register_generator_implementation(&mut self, generator_id: u8, implementation: u8) {
+        self.generator_ids.push(U8::from(generator_id));
+        self.generators.insert(generator_id, U8::from(implementation + 1));
+    }
+
+    /// Replaces the colors of a configurable palette
+    fn set_palette_colors(&mut self, palette: usize, colors: &[u32]) {
+        let mut stored_colors = self.palettes.setter(palette).unwrap();
+        stored_colors.truncate(0);
+        for &color in colors {
+            stored_colors.push(U32::from(color));
+        }
+    }
+
+    /// Stores the centers (x, y) of the left eye, right eye and mouth
+    fn set_positions(&mut self, positions: [u8; 6]) {
+        for (index, position) in positions.into_iter().enumerate() {
+            self.feature_positions.setter(index).unwrap().set(U8::from(position));
+        }
+    }
+
+    /// Stores the rarity weights
+    fn set_weights(&mut self, weights: &RarityWeights) {
+        for (index, weight) in weights.to_vec().into_iter().enumerate() {
+            self.rarity_weights.setter(index).unwrap().set(U32::from(weight));
+        }
+    }
+}
+
+// Contract implementation
+#[public]
+#[inherit(PixelCanvas)]
+impl StylusNFTArt {
+    /// Generates the art of a specific token_id
+    #[selector(name = "generateArt")]
+    pub fn generate_art(&self, token_id: U256) -> Result<String, Vec<u8>> {
+        let owner = self.art_owner(token_id)?;
         let image_str = self.generate_art_with_owner(token_id, owner)?;
         Ok(image_str)
     }
     
     /// Generates the art of a specific token_id and a specific address (assuming it's the owner)
     #[selector(name = "generateArt")]
-    pub fn generate_art_with_owner(&mut self, token_id: U256, owner: Address) -> Result<String, StylusNftArtError> {
-        let image = gen_art(owner, token_id);
-        let image_png = image.make_png();
-        let mut image_str = String::from("data:image/png;base64,");
-        base64::engine::general_purpose::STANDARD.encode_string(&image_png, &mut image_str);
-        Ok(image_str)
+    pub fn generate_art_with_owner(&self, token_id: U256, owner: Address) -> Result<String, Vec<u8>> {
+        self.token_art_uri(token_id, owner, &mut BTreeMap::new())
+    }
+
+    /// Generates the art of a specific token_id as raw bytes in a format (see `getSupportedFormats`),
+    /// for the account its art is rendered for
+    pub fn generate_art_as(&self, token_id: U256, format: u8) -> Result<Bytes, Vec<u8>> {
+        let owner = self.art_owner(token_id)?;
+        self.generate_art_as_with_owner(token_id, owner, format)
+    }
+
+    /// Generates the art of a specific token_id and a specific address (assuming it's the owner) as raw bytes:
+    /// 0 = PNG, 1 = RGB pixels (row by row), 2 = keccak256 of the PNG, 3 = SVG and 4 = GIF
+    #[selector(name = "generateArtAs")]
+    pub fn generate_art_as_with_owner(&self, token_id: U256, owner: Address, format: u8) -> Result<Bytes, Vec<u8>> {
+        let format = ArtFormat::from_id(format).ok_or(StylusNftArtError::UnsupportedFormat(UnsupportedFormat { format }))?;
+        let (_, generator) = self.token_generator(token_id);
+        let seed = self.token_seed(token_id, owner)?;
+        let (stage, tier) = self.token_overlays(token_id, owner, generator, &mut BTreeMap::new())?;
+        // The PNG formats can use the cached image
+        let bytes = match format {
+            ArtFormat::Png => self.token_png(token_id, owner, &seed, generator, stage, tier),
+            ArtFormat::PngKeccak => {
+                crypto::keccak(self.token_png(token_id, owner, &seed, generator, stage, tier)).to_vec()
+            }
+            format => format.encode(&self.token_image(token_id, &seed, generator, stage, tier)),
+        };
+        Ok(bytes.into())
+    }
+
+    /// Returns the ids of the formats `generateArtAs` supports, which depend on the features the contract was built with
+    pub fn get_supported_formats(&self) -> Result<Vec<u8>, StylusNftArtError> {
+        Ok(SUPPORTED_FORMATS.to_vec())
+    }
+
+    /// Generates the metadata JSON document of a specific token_id, as a `data:` URI
+    #[selector(name = "generateMetadata")]
+    pub fn generate_metadata(&self, token_id: U256) -> Result<String, Vec<u8>> {
+        let owner = self.art_owner(token_id)?;
+        self.generate_metadata_with_owner(token_id, owner)
+    }
+
+    /// Generates the metadata JSON document of a specific token_id and a specific address (assuming it's the owner), as a `data:` URI
+    #[selector(name = "generateMetadata")]
+    pub fn generate_metadata_with_owner(&self, token_id: U256, owner: Address) -> Result<String, Vec<u8>> {
+        self.token_metadata_uri(token_id, owner, &mut BTreeMap::new())
+    }
+
+    /// Generates the art of up to `MAX_BATCH_SIZE` tokens, given the owner of each one
+    /// (with a call to the ERC-20 contract per holder, for their tiers)
+    pub fn generate_art_batch(&self, token_ids: Vec<U256>, owners: Vec<Address>) -> Result<Vec<String>, Vec<u8>> {
+        Self::check_batch(&token_ids, &owners, MAX_BATCH_SIZE)?;
+        let mut tiers = BTreeMap::new();
+        token_ids
+            .into_iter()
+            .zip(owners)
+            .map(|(token_id, owner)| self.token_art_uri(token_id, owner, &mut tiers))
+            .collect()
+    }
+
+    /// Generates the metadata JSON documents of up to `MAX_BATCH_SIZE` tokens, given the owner of each one
+    /// (with a call to the ERC-20 contract per holder, for their tiers)
+    pub fn generate_metadata_batch(&self, token_ids: Vec<U256>, owners: Vec<Address>) -> Result<Vec<String>, Vec<u8>> {
+        Self::check_batch(&token_ids, &owners, MAX_BATCH_SIZE)?;
+        let mut tiers = BTreeMap::new();
+        token_ids
+            .into_iter()
+            .zip(owners)
+            .map(|(token_id, owner)| self.token_metadata_uri(token_id, owner, &mut tiers))
+            .collect()
+    }
+
+    /// Generates the placeholder shown instead of the art of the tokens of an unrevealed collection, as a `data:` URI
+    pub fn generate_placeholder_art(&self) -> Result<String, StylusNftArtError> {
+        Ok(make_image_uri(&make_placeholder(&self.art_config())))
+    }
+
+    /// Generates the metadata JSON document of a token of an unrevealed collection, as a `data:` URI
+    pub fn generate_placeholder_metadata(&self, token_id: U256) -> Result<String, StylusNftArtError> {
+        let image_uri = make_image_uri(&make_placeholder(&self.art_config()));
+        let name = METADATA_NAME_PREFIX.to_string() + &token_id.to_string();
+        let json = make_metadata_json(&name, PLACEHOLDER_DESCRIPTION, &image_uri, &[]);
+        Ok(make_metadata_uri(&json))
+    }
+
+    /// Returns the traits of a specific token_id
+    #[selector(name = "getTraits")]
+    pub fn get_traits(&self, token_id: U256) -> Result<(u8, u8, u8, u8), Vec<u8>> {
+        let owner = self.art_owner(token_id)?;
+        Ok(self.get_traits_with_owner(token_id, owner)?)
+    }
+
+    /// Returns the traits (background, eyes, mouth, palette) of a specific token_id and a specific address (assuming it's the owner)
+    #[selector(name = "getTraits")]
+    pub fn get_traits_with_owner(&self, token_id: U256, owner: Address) -> Result<(u8, u8, u8, u8), StylusNftArtError> {
+        let (_, generator) = self.token_generator(token_id);
+        let seed = self.token_seed(token_id, owner)?;
+        let traits = generator.traits(&seed, &self.rarity_weights(), &self.token_options(token_id), self.token_parent_traits(token_id).as_ref());
+        Ok((traits.background, traits.eyes, traits.mouth, traits.palette))
+    }
+
+    /// Renders a grid of up to `MAX_PREVIEW_TOKENS` tokens, labeled with their ids, as a PNG `data:` URI.
+    /// `owners` holds the owner of each token, used by the tokens of art version 1.
+    /// Tokens whose seed has not been revealed yet are drawn as a blank tile.
+    pub fn collection_preview(&self, token_ids: Vec<U256>, owners: Vec<Address>) -> Result<String, Vec<u8>> {
+        Self::check_batch(&token_ids, &owners, MAX_PREVIEW_TOKENS)?;
+        self.contact_sheet_uri(&token_ids, &owners)
+    }
+
+    /// Renders the art of the first `MAX_PORTRAIT_TOKENS` tokens held by `owner` (in the order of
+    /// `tokenOfOwnerByIndex`) into a single image labeled with their ids, as a PNG `data:` URI.
+    /// Each token is drawn as in its tokenURI, for the account its art is rendered for.
+    /// Only the tokens bound to this Art contract are drawn: the ones bound to another Art contract
+    /// after a migration are skipped, since this one doesn't hold their art.
+    /// The NFT contract is called a bounded number of times, however many tokens `owner` holds.
+    pub fn owner_portrait(&self, owner: Address) -> Result<String, Vec<u8>> {
+        let token_contract_address = self.token_contract_address.get();
+        let token_contract = Nft::new(token_contract_address);
+        let config = call_with_gas_limit(self.nft_call_gas_limit.get().to::<u64>());
+        let balance = token_contract
+            .balance_of(config, owner)
+            .map_err(|e| self.call_failed(token_contract_address, function_selector!("balanceOf", Address), e))?;
+        let count = balance.min(U256::from(MAX_PORTRAIT_TOKENS)).to::<usize>();
+        let mut token_ids = Vec::with_capacity(count);
+        for index in 0..count {
+            let config = call_with_gas_limit(self.nft_call_gas_limit.get().to::<u64>());
+            let token_id = token_contract.token_of_owner_by_index(config, owner, U256::from(index)).map_err(|e| {
+                self.call_failed(token_contract_address, function_selector!("tokenOfOwnerByIndex", Address, U256), e)
+            })?;
+            let config = call_with_gas_limit(self.nft_call_gas_limit.get().to::<u64>());
+            let art_contract = token_contract
+                .art_contract_of(config, token_id)
+                .map_err(|e| self.call_failed(token_contract_address, function_selector!("artContractOf", U256), e))?;
+            if art_contract == contract::address() {
+                token_ids.push(token_id);
+            }
+        }
+        let art_owners = token_ids
+            .iter()
+            .map(|&token_id| self.art_owner(token_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.contact_sheet_uri(&token_ids, &art_owners)
+    }
+
+    /// Generates the art of a token of a registered ERC-721 collection, as a PNG `data:` URI.
+    /// The art is derived from the collection address, the token id and the owner returned by its `ownerOf`.
+    pub fn generate_art_for(&self, collection: Address, token_id: U256) -> Result<String, Vec<u8>> {
+        let (seed, generator) = self.collection_token_art(collection, token_id)?;
+        let config = self.collection_art_config(collection);
+        let (image, _) = gen_art(&seed, generator, &config, &ArtOptions::default(), None, 0, 0);
+        Ok(make_image_uri(&image))
+    }
+
+    /// Generates the metadata JSON document of a token of a registered ERC-721 collection, as a `data:` URI
+    pub fn generate_metadata_for(&self, collection: Address, token_id: U256) -> Result<String, Vec<u8>> {
+        let (seed, generator) = self.collection_token_art(collection, token_id)?;
+        let config = self.collection_art_config(collection);
+        let (image, traits) = gen_art(&seed, generator, &config, &ArtOptions::default(), None, 0, 0);
+        let name = self.collections.getter(collection).name.get_string() + " #" + &token_id.to_string();
+        let mut attributes = traits.attributes().to_vec();
+        attributes.push(("Style", generator.name()));
+        let json = make_metadata_json(&name, METADATA_DESCRIPTION, &make_image_uri(&image), &attributes);
+        Ok(make_metadata_uri(&json))
+    }
+
+    /// Registers an ERC-721 collection, so the art of its tokens can be generated with `generateArtFor` and
+    /// `generateMetadataFor`. Its tokens are named `name` followed by their id in their metadata.
+    /// Registering a collection again renames it.
+    pub fn register_collection(&mut self, collection: Address, name: String) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_COLLECTION_MANAGER)?;
+        let known = (0..self.collection_addresses.len()).any(|i| self.collection_addresses.get(i) == Some(collection));
+        let generator_count = self.generator_ids.len();
+        let mut style = self.collections.setter(collection);
+        if !known {
+            self.collection_addresses.push(collection);
+            // Its tokens pick their generator among the ones registered now, so later ones don't restyle them
+            style.generator_count.set(U8::from(generator_count));
+        }
+        style.registered.set(true);
+        style.name.set_str(&name);
+        evm::log(CollectionRegistered { collection, name });
+        Ok(())
+    }
+
+    /// Stops rendering the art of the tokens of an ERC-721 collection
+    pub fn unregister_collection(&mut self, collection: Address) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_COLLECTION_MANAGER)?;
+        let mut style = self.collections.setter(collection);
+        if !style.registered.get() {
+            return Err(StylusNftArtError::UnknownCollection(UnknownCollection { collection }));
+        }
+        style.registered.set(false);
+        evm::log(CollectionUnregistered { collection });
+        Ok(())
+    }
+
+    /// Chooses the generator used by all the tokens of a registered ERC-721 collection
+    pub fn set_collection_generator(&mut self, collection: Address, generator_id: u8) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_COLLECTION_MANAGER)?;
+        if self.generators.get(generator_id).is_zero() {
+            return Err(StylusNftArtError::UnknownGenerator(UnknownGenerator { generator_id }));
+        }
+        let mut style = self.collections.setter(collection);
+        if !style.registered.get() {
+            return Err(StylusNftArtError::UnknownCollection(UnknownCollection { collection }));
+        }
+        style.generator.set(U8::from(generator_id + 1));
+        evm::log(CollectionGeneratorChosen { collection, generator_id });
+        Ok(())
+    }
+
+    /// Draws the art of the tokens of a registered ERC-721 collection with its own background color and gradient
+    /// stops (0xRRGGBB), or with the ones of the Art contract again if `custom` is false
+    pub fn set_collection_colors(
+        &mut self,
+        collection: Address,
+        custom: bool,
+        background: u32,
+        gradient_start: u32,
+        gradient_end: u32,
+    ) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_COLLECTION_MANAGER)?;
+        let mut style = self.collections.setter(collection);
+        if !style.registered.get() {
+            return Err(StylusNftArtError::UnknownCollection(UnknownCollection { collection }));
+        }
+        let colors = [background, gradient_start, gradient_end].map(|color| if custom { color & 0xffffff } else { 0 });
+        style.custom_colors.set(custom);
+        for (index, color) in colors.into_iter().enumerate() {
+            style.colors.setter(index).unwrap().set(U32::from(color));
+        }
+        let [background, gradient_start, gradient_end] = colors;
+        evm::log(CollectionColorsUpdated { collection, custom, background, gradient_start, gradient_end });
+        Ok(())
+    }
+
+    /// Returns whether a registered ERC-721 collection has its own colors, and its background color and gradient stops
+    pub fn get_collection_colors(&self, collection: Address) -> Result<(bool, u32, u32, u32), StylusNftArtError> {
+        let style = self.collections.getter(collection);
+        let color = |index: usize| style.colors.get(index).unwrap_or_default().to::<u32>();
+        Ok((style.custom_colors.get(), color(0), color(1), color(2)))
+    }
+
+    /// Returns whether an ERC-721 collection is registered, the name of its tokens, and its generator id + 1
+    /// (0 if the generator is picked from each token id)
+    pub fn get_collection(&self, collection: Address) -> Result<(bool, String, u8), StylusNftArtError> {
+        let style = self.collections.getter(collection);
+        Ok((style.registered.get(), style.name.get_string(), style.generator.get().to::<u8>()))
+    }
+
+    /// Returns the addresses of the ERC-721 collections that have ever been registered
+    pub fn get_collections(&self) -> Result<Vec<Address>, StylusNftArtError> {
+        Ok((0..self.collection_addresses.len())
+            .map(|i| self.collection_addresses.get(i).unwrap_or_default())
+            .collect())
     }
 
     /// Initialize program
-    pub fn initialize(&mut self, token_contract_address: Address) -> Result<(), StylusNftArtError> {
-        let current_contract = self.token_contract_address.get();
-        if !current_contract.is_zero() {
-            return Err(StylusNftArtError::AlreadyInitialized(AlreadyInitialized {}));
+    ///
+    /// The account that initializes the contract becomes its admin.
+    /// If the contract was built with a designated admin (see [`DESIGNATED_ADMIN`]), only it can initialize it.
+    /// The default balance tiers are whole ERC-20 tokens, scaled by the `decimals` of the ERC-20 contract.
+    pub fn initialize(&mut self, token_contract_address: Address, erc20_token_contract_address: Address) -> Result<(), Vec<u8>> {
+        if !self.admin.get().is_zero() || !self.token_contract_address.get().is_zero() {
+            return Err(StylusNftArtError::AlreadyInitialized(AlreadyInitialized {}).into());
+        }
+        let admin = msg::sender();
+        if DESIGNATED_ADMIN.is_some_and(|designated_admin| admin != designated_admin) {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: admin }).into());
+        }
+        let erc20_decimals = if erc20_token_contract_address.is_zero() {
+            DEFAULT_ERC20_DECIMALS
+        } else {
+            let config = call_with_gas_limit(self.erc20_call_gas_limit.get().to::<u64>());
+            Erc20::new(erc20_token_contract_address)
+                .decimals(config)
+                .map_err(|e| self.call_failed(erc20_token_contract_address, function_selector!("decimals"), e))?
+        };
+        self.token_contract_address.set(token_contract_address);
+        self.erc20_token_contract_address.set(erc20_token_contract_address);
+        self.admin.set(admin);
+        evm::log(AdminTransferred { previous_admin: Address::ZERO, new_admin: admin });
+
+        // Store the default parameters of the art generator
+        let config = ArtConfig::default();
+        self.background_color.set(U32::from(config.background.to_hex()));
+        self.gradient_stops.setter(0).unwrap().set(U32::from(config.gradient_start.to_hex()));
+        self.gradient_stops.setter(1).unwrap().set(U32::from(config.gradient_end.to_hex()));
+        for (index, colors) in config.palettes.iter().enumerate() {
+            let colors: Vec<u32> = colors.iter().map(|color| color.to_hex() as u32).collect();
+            self.set_palette_colors(index, &colors);
+        }
+        self.set_positions([
+            config.left_eye.x() as u8,
+            config.left_eye.y() as u8,
+            config.right_eye.x() as u8,
+            config.right_eye.y() as u8,
+            config.mouth.x() as u8,
+            config.mouth.y() as u8,
+        ]);
+
+        self.set_weights(&config.rarity_weights);
+        self.placeholder_colors.setter(0).unwrap().set(U32::from(config.placeholder_background.to_hex()));
+        self.placeholder_colors.setter(1).unwrap().set(U32::from(config.placeholder_foreground.to_hex()));
+        self.set_thresholds(&DEFAULT_STAGE_THRESHOLDS);
+        self.set_tiers(&default_tier_thresholds(erc20_decimals));
+        self.canvas.set_palette(&DEFAULT_CANVAS_PALETTE);
+        self.canvas.set_cooldown(DEFAULT_PAINT_COOLDOWN);
+
+        // Register the built-in generators
+        for implementation in 0..BUILTIN_GENERATORS.len() as u8 {
+            self.register_generator_implementation(implementation, implementation);
+        }
+        Ok(())
+    }
+
+    /// Records the art version and the generator of a newly minted token,
+    /// so its art doesn't change when newer versions or generators are added.
+    /// Only the NFT contract can register tokens.
+    pub fn register_token(&mut self, token_id: U256) -> Result<(), StylusNftArtError> {
+        self.only_token_contract()?;
+        if !self.token_versions.get(token_id).is_zero() {
+            return Err(StylusNftArtError::AlreadyRegistered(AlreadyRegistered { token_id }));
+        }
+        let version = LATEST_ART_VERSION;
+        self.token_versions.insert(token_id, U8::from(version));
+
+        let generator_id = self.random_generator_id(token_id, self.generator_ids.len());
+        self.token_generators.insert(token_id, U8::from(generator_id + 1));
+        // Minting is the first transfer of the token
+        self.token_held_since.insert(token_id, U64::from(block::timestamp()));
+        evm::log(TokenRegistered { token_id, version, generator_id });
+        Ok(())
+    }
+
+    /// Stores the keccak-based seed revealed for a token, used from art version 2 on.
+    /// Only the NFT contract can set seeds, and only once per token.
+    pub fn set_token_seed(&mut self, token_id: U256, seed: B256) -> Result<(), StylusNftArtError> {
+        self.only_token_contract()?;
+        if !self.token_seeds.get(token_id).is_zero() {
+            return Err(StylusNftArtError::AlreadyRevealed(AlreadyRevealed { token_id }));
+        }
+        // Bred tokens mix the seeds of their parents into the revealed one
+        let seed = match self.token_parents(token_id) {
+            Some(parents) => child_seed(parents.map(|parent| self.token_seeds.get(parent)), seed),
+            None => seed,
+        };
+        self.token_seeds.insert(token_id, seed);
+        self.clear_cached_image(token_id);
+        evm::log(TokenSeedSet { token_id, seed });
+        Ok(())
+    }
+
+    /// Records the parents of a token minted by breeding them, before its seed is revealed.
+    /// Both parents must have been revealed. The child inherits their traits, and the style of one of them.
+    /// Only the NFT contract can register children.
+    pub fn register_child(&mut self, token_id: U256, parent_a: U256, parent_b: U256) -> Result<(), StylusNftArtError> {
+        self.only_token_contract()?;
+        if self.token_parents(token_id).is_some() || !self.token_seeds.get(token_id).is_zero() {
+            return Err(StylusNftArtError::AlreadyRegistered(AlreadyRegistered { token_id }));
+        }
+        let parent_traits = [self.revealed_traits(parent_a)?, self.revealed_traits(parent_b)?];
+        let parents = [parent_a, parent_b];
+        let style_parent = parents[(token_id_hash(token_id) % parents.len() as u64) as usize];
+        // Copies the stored generator, so the child of a legacy token is drawn by the legacy generator too
+        let generator = self.token_generators.get(style_parent);
+        self.token_generators.insert(token_id, generator);
+
+        let mut stored_parents = self.token_parents.setter(token_id);
+        for (index, parent) in parents.into_iter().enumerate() {
+            stored_parents.setter(index).unwrap().set(parent);
+        }
+        self.token_parent_traits.setter(token_id).set_bytes(encode_parent_traits(&parent_traits));
+        evm::log(TokenBred { token_id, parent_a, parent_b });
+        Ok(())
+    }
+
+    /// Returns the ids of the parents of a token, and whether it was bred at all
+    pub fn get_token_parents(&self, token_id: U256) -> Result<(bool, U256, U256), StylusNftArtError> {
+        Ok(match self.token_parents(token_id) {
+            Some([parent_a, parent_b]) => (true, parent_a, parent_b),
+            None => (false, U256::ZERO, U256::ZERO),
+        })
+    }
+
+    /// Records the account holding a token after its last transfer, whose ERC-20 balance gives the tier of its art,
+    /// and the timestamp since which it holds it, and removes its cached image. Only the NFT contract can set them.
+    pub fn set_token_holder(&mut self, token_id: U256, holder: Address, timestamp: u64) -> Result<(), StylusNftArtError> {
+        self.only_token_contract()?;
+        self.token_holders.insert(token_id, holder);
+        self.token_held_since.insert(token_id, U64::from(timestamp));
+        self.clear_cached_image(token_id);
+        Ok(())
+    }
+
+    /// Stores the options of the art of a token, encoded as described in [`ArtOptions`].
+    /// Empty options restore the art picked from the seed. Only the NFT contract can set them,
+    /// on behalf of the owner of the token or an account approved by the owner.
+    /// The art drawn by the legacy generator is frozen, so its tokens only accept empty options.
+    pub fn set_token_options(&mut self, token_id: U256, options: Bytes) -> Result<(), StylusNftArtError> {
+        self.only_token_contract()?;
+        let decoded = ArtOptions::decode(&options).ok_or(StylusNftArtError::InvalidArtOptions(InvalidArtOptions {}))?;
+        if self.token_generator(token_id).0 == LEGACY_GENERATOR_ID && decoded != ArtOptions::default() {
+            return Err(StylusNftArtError::InvalidArtOptions(InvalidArtOptions {}));
+        }
+        self.token_options.setter(token_id).set_bytes(decoded.encode());
+        self.clear_cached_image(token_id);
+        evm::log(TokenOptionsSet { token_id, options: decoded.encode().into() });
+        Ok(())
+    }
+
+    /// Returns the encoded options of the art of a token (empty if none)
+    pub fn get_token_options(&self, token_id: U256) -> Result<Bytes, StylusNftArtError> {
+        Ok(self.token_options.getter(token_id).get_bytes().into())
+    }
+
+    /// Returns the timestamp since which a token is held by its current owner (0 if unknown)
+    pub fn get_token_held_since(&self, token_id: U256) -> Result<u64, StylusNftArtError> {
+        Ok(self.token_held_since.get(token_id).to::<u64>())
+    }
+
+    /// Returns the stage reached by a token (0 = Fresh, 1 = Settled, 2 = Seasoned, 3 = Legendary)
+    pub fn get_token_stage(&self, token_id: U256) -> Result<u8, StylusNftArtError> {
+        Ok(self.token_stage(token_id))
+    }
+
+    /// Sets the number of seconds a token must be held to reach each stage after the first one.
+    /// Thresholds must be increasing, and there can be at most one per stage after the first one.
+    pub fn set_stage_thresholds(&mut self, thresholds: Vec<u64>) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let increasing = thresholds.windows(2).all(|pair| pair[0] < pair[1]);
+        if thresholds.len() > MAX_STAGE as usize || !increasing {
+            return Err(StylusNftArtError::InvalidStageThresholds(InvalidStageThresholds {}));
+        }
+        self.set_thresholds(&thresholds);
+        evm::log(StageThresholdsUpdated { thresholds });
+        Ok(())
+    }
+
+    /// Returns the number of seconds a token must be held to reach each stage after the first one
+    pub fn get_stage_thresholds(&self) -> Result<Vec<u64>, StylusNftArtError> {
+        Ok(self.stage_thresholds())
+    }
+
+    /// Sets the minimum ERC-20 balance needed to reach each tier after the first one.
+    /// Thresholds must be increasing, and there can be at most one per tier after the first one.
+    pub fn set_balance_tiers(&mut self, thresholds: Vec<U256>) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let increasing = thresholds.windows(2).all(|pair| pair[0] < pair[1]);
+        if thresholds.len() > MAX_TIER as usize || !increasing {
+            return Err(StylusNftArtError::InvalidBalanceTiers(InvalidBalanceTiers {}));
+        }
+        self.set_tiers(&thresholds);
+        evm::log(BalanceTiersUpdated { thresholds });
+        Ok(())
+    }
+
+    /// Returns the minimum ERC-20 balance needed to reach each tier after the first one
+    pub fn get_balance_tiers(&self) -> Result<Vec<U256>, StylusNftArtError> {
+        Ok(self.balance_tiers())
+    }
+
+    /// Returns the tier reached by the ERC-20 balance of an account (0 = None, 1 = Bronze, 2 = Silver, 3 = Diamond)
+    pub fn get_owner_tier(&self, owner: Address) -> Result<u8, Vec<u8>> {
+        self.owner_tier(owner)
+    }
+
+    /// Returns the keccak-based seed revealed for a token (zero if not revealed)
+    pub fn get_token_seed(&self, token_id: U256) -> Result<B256, StylusNftArtError> {
+        Ok(self.token_seeds.get(token_id))
+    }
+
+    /// Returns the art version a token was minted with
+    pub fn get_token_version(&self, token_id: U256) -> Result<u8, StylusNftArtError> {
+        Ok(self.token_version(token_id))
+    }
+
+    /// Maps a new generator id to a built-in implementation (0 = Face, 1 = Stripes, 2 = Cyclops).
+    /// A registered id can't be mapped to another implementation, since that would restyle the tokens using it:
+    /// register a new id instead.
+    pub fn register_generator(&mut self, generator_id: u8, implementation: u8) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        if implementation as usize >= BUILTIN_GENERATORS.len() || generator_id == LEGACY_GENERATOR_ID {
+            return Err(StylusNftArtError::UnknownGenerator(UnknownGenerator { generator_id }));
+        }
+        if !self.generators.get(generator_id).is_zero() {
+            return Err(StylusNftArtError::GeneratorAlreadyRegistered(GeneratorAlreadyRegistered { generator_id }));
+        }
+        self.register_generator_implementation(generator_id, implementation);
+        evm::log(GeneratorRegistered { generator_id, implementation });
+        Ok(())
+    }
+
+    /// Chooses the generator used by a token. Only the owner of the token can choose it.
+    pub fn set_token_generator(&mut self, token_id: U256, generator_id: u8) -> Result<(), Vec<u8>> {
+        let owner = self.token_owner(token_id)?;
+        if msg::sender() != owner {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: msg::sender() }).into());
+        }
+        if self.generators.get(generator_id).is_zero() {
+            return Err(StylusNftArtError::UnknownGenerator(UnknownGenerator { generator_id }).into());
+        }
+        self.token_generators.insert(token_id, U8::from(generator_id + 1));
+        self.clear_cached_image(token_id);
+        evm::log(TokenGeneratorChosen { token_id, generator_id });
+        Ok(())
+    }
+
+    /// Returns the ids of the registered generators
+    pub fn get_generators(&self) -> Result<Vec<u8>, StylusNftArtError> {
+        Ok((0..self.generator_ids.len())
+            .map(|i| self.generator_ids.get(i).unwrap_or_default().to::<u8>())
+            .collect())
+    }
+
+    /// Returns the name of the style drawn by a registered generator
+    pub fn get_generator_name(&self, generator_id: u8) -> Result<String, StylusNftArtError> {
+        if generator_id == LEGACY_GENERATOR_ID {
+            return Ok(LegacyGenerator.name().into());
+        }
+        let implementation = self.generators.get(generator_id).to::<usize>();
+        match BUILTIN_GENERATORS.get(implementation.wrapping_sub(1)) {
+            Some(generator) => Ok(generator.name().into()),
+            None => Err(StylusNftArtError::UnknownGenerator(UnknownGenerator { generator_id })),
+        }
+    }
+
+    /// Returns the id of the generator used by a token (`LEGACY_GENERATOR_ID` for the tokens that were not registered
+    /// at mint time)
+    pub fn get_token_generator(&self, token_id: U256) -> Result<u8, StylusNftArtError> {
+        Ok(self.token_generator(token_id).0)
+    }
+
+    /// Enables or disables the cache of generated images
+    pub fn set_cache_enabled(&mut self, enabled: bool) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        self.cache_enabled.set(enabled);
+        evm::log(CacheEnabledUpdated { enabled });
+        Ok(())
+    }
+
+    /// Returns true if generated images are cached
+    pub fn is_cache_enabled(&self) -> Result<bool, StylusNftArtError> {
+        Ok(self.cache_enabled.get())
+    }
+
+    /// Returns true if the image of a token is cached for a specific owner, the current configuration, its current stage and the tier of the owner
+    pub fn is_cached(&self, token_id: U256, owner: Address) -> Result<bool, Vec<u8>> {
+        let (_, generator) = self.token_generator(token_id);
+        let (stage, tier) = self.token_overlays(token_id, owner, generator, &mut BTreeMap::new())?;
+        Ok(self.cached_png(token_id, owner, stage, tier).is_some())
+    }
+
+    /// Renders the image of a token for the account its art is rendered for, and stores it in the cache.
+    /// Anyone can warm the cache, since the image is always rendered for the account the NFT contract returns.
+    pub fn warm_cache(&mut self, token_id: U256) -> Result<(), Vec<u8>> {
+        if !self.cache_enabled.get() {
+            return Err(StylusNftArtError::CacheDisabled(CacheDisabled {}).into());
+        }
+        let owner = self.art_owner(token_id)?;
+        let (_, generator) = self.token_generator(token_id);
+        let seed = self.token_seed(token_id, owner)?;
+        let (stage, tier) = self.token_overlays(token_id, owner, generator, &mut BTreeMap::new())?;
+        let image_png = self.render_token(token_id, &seed, generator, stage, tier);
+        self.cached_images.setter(token_id).set_bytes(&image_png);
+        self.cached_owners.insert(token_id, owner);
+        self.cached_epochs.insert(token_id, self.cache_epoch.get());
+        self.cached_stages.insert(token_id, U8::from(stage));
+        self.cached_tiers.insert(token_id, U8::from(tier));
+        Ok(())
+    }
+
+    /// Removes the cached image of a token, e.g. when it's transferred.
+    /// Only the NFT contract, the admin and the art managers can invalidate the cache.
+    pub fn invalidate_cache(&mut self, token_id: U256) -> Result<(), StylusNftArtError> {
+        if msg::sender() != self.token_contract_address.get() {
+            self.only_role(ROLE_ART_MANAGER)?;
+        }
+        self.clear_cached_image(token_id);
+        evm::log(CacheInvalidated { token_id });
+        Ok(())
+    }
+
+    /// Sets the color of the "Solid" background (0xRRGGBB)
+    pub fn set_background_color(&mut self, color: u32) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        self.background_color.set(U32::from(color & 0xffffff));
+        self.bump_cache_epoch();
+        evm::log(BackgroundColorUpdated { color: color & 0xffffff });
+        Ok(())
+    }
+
+    /// Sets the first and last stops (0xRRGGBB) of the gradient backgrounds
+    pub fn set_gradient(&mut self, start: u32, end: u32) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let (start, end) = (start & 0xffffff, end & 0xffffff);
+        self.gradient_stops.setter(0).unwrap().set(U32::from(start));
+        self.gradient_stops.setter(1).unwrap().set(U32::from(end));
+        self.bump_cache_epoch();
+        evm::log(GradientUpdated { start, end });
+        Ok(())
+    }
+
+    /// Sets the colors (0xRRGGBB) of a palette (1 = Warm, 2 = Cool, 3 = Mono)
+    pub fn set_palette(&mut self, palette: u8, colors: Vec<u32>) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let palette_index = (palette as usize).wrapping_sub(1);
+        if palette_index >= CONFIGURABLE_PALETTES || colors.is_empty() || colors.len() > MAX_PALETTE_COLORS {
+            return Err(StylusNftArtError::InvalidPalette(InvalidPalette {
+                palette,
+                colors: U256::from(colors.len()),
+            }));
+        }
+        let colors: Vec<u32> = colors.iter().map(|color| color & 0xffffff).collect();
+        self.set_palette_colors(palette_index, &colors);
+        self.bump_cache_epoch();
+        evm::log(PaletteUpdated { palette, colors });
+        Ok(())
+    }
+
+    /// Sets the centers of the eyes and the mouth
+    pub fn set_feature_positions(
+        &mut self,
+        left_eye_x: u8,
+        left_eye_y: u8,
+        right_eye_x: u8,
+        right_eye_y: u8,
+        mouth_x: u8,
+        mouth_y: u8,
+    ) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        for (x, y) in [(left_eye_x, left_eye_y), (right_eye_x, right_eye_y)] {
+            if !is_valid_eye_position(x, y) {
+                return Err(StylusNftArtError::InvalidFeaturePosition(InvalidFeaturePosition { x, y }));
+            }
+        }
+        if !is_valid_mouth_position(mouth_x, mouth_y) {
+            return Err(StylusNftArtError::InvalidFeaturePosition(InvalidFeaturePosition { x: mouth_x, y: mouth_y }));
+        }
+        self.set_positions([left_eye_x, left_eye_y, right_eye_x, right_eye_y, mouth_x, mouth_y]);
+        self.bump_cache_epoch();
+        evm::log(FeaturePositionsUpdated { left_eye_x, left_eye_y, right_eye_x, right_eye_y, mouth_x, mouth_y });
+        Ok(())
+    }
+
+    /// Sets the relative weights used to pick each background, eyes, mouth and palette (one weight per value,
+    /// in the order of their names). The traits of all the tokens are picked again with the new weights.
+    pub fn set_rarity_weights(
+        &mut self,
+        background: Vec<u32>,
+        eyes: Vec<u32>,
+        mouth: Vec<u32>,
+        palette: Vec<u32>,
+    ) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let weights = RarityWeights::new(&background, &eyes, &mouth, &palette)
+            .ok_or(StylusNftArtError::InvalidRarityWeights(InvalidRarityWeights {}))?;
+        self.set_weights(&weights);
+        self.bump_cache_epoch();
+        evm::log(RarityWeightsUpdated { background, eyes, mouth, palette });
+        Ok(())
+    }
+
+    /// Sets the background and foreground colors (0xRRGGBB) of the placeholder of unrevealed tokens
+    pub fn set_placeholder_colors(&mut self, background: u32, foreground: u32) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let (background, foreground) = (background & 0xffffff, foreground & 0xffffff);
+        self.placeholder_colors.setter(0).unwrap().set(U32::from(background));
+        self.placeholder_colors.setter(1).unwrap().set(U32::from(foreground));
+        evm::log(PlaceholderUpdated { background, foreground });
+        Ok(())
+    }
+
+    /// Returns the background and foreground colors of the placeholder of unrevealed tokens
+    pub fn get_placeholder_colors(&self) -> Result<(u32, u32), StylusNftArtError> {
+        let config = self.art_config();
+        Ok((config.placeholder_background.to_hex() as u32, config.placeholder_foreground.to_hex() as u32))
+    }
+
+    /// Paints a pixel of the collaborative canvas with a color of its palette (by index), on behalf of a token.
+    /// Only the owner of the token can paint with it, and each token must wait the paint cooldown between two pixels.
+    pub fn paint_pixel(&mut self, token_id: U256, x: u8, y: u8, color: u8) -> Result<(), Vec<u8>> {
+        let sender = msg::sender();
+        if self.token_owner(token_id)? != sender {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: sender }).into());
+        }
+        if !is_valid_canvas_pixel(x, y) {
+            return Err(StylusNftArtError::InvalidCanvasPixel(InvalidCanvasPixel { x, y }).into());
+        }
+        if color as usize >= CANVAS_COLORS {
+            return Err(StylusNftArtError::InvalidCanvasColor(InvalidCanvasColor { color }).into());
+        }
+        let now = block::timestamp();
+        let next_paint_time = self.canvas.next_paint_time(token_id);
+        if now < next_paint_time {
+            return Err(StylusNftArtError::PaintCooldown(PaintCooldown { token_id, next_paint_time }).into());
+        }
+        self.canvas.set_pixel(x as usize, y as usize, color);
+        self.canvas.record_paint(token_id, now);
+        evm::log(PixelPainted { token_id, painter: sender, x, y, color });
+        Ok(())
+    }
+
+    /// Sets the 16 colors (0xRRGGBB) of the palette of the canvas. The pixels keep their palette index.
+    pub fn set_canvas_palette(&mut self, colors: Vec<u32>) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        let colors: Vec<u32> = colors.iter().map(|color| color & 0xffffff).collect();
+        let palette: [u32; CANVAS_COLORS] = colors.as_slice().try_into().map_err(|_| {
+            StylusNftArtError::InvalidCanvasPalette(InvalidCanvasPalette { colors: U256::from(colors.len()) })
+        })?;
+        self.canvas.set_palette(&palette);
+        evm::log(CanvasPaletteUpdated { colors });
+        Ok(())
+    }
+
+    /// Sets the number of seconds a token must wait between two pixels of the canvas
+    pub fn set_paint_cooldown(&mut self, cooldown: u64) -> Result<(), StylusNftArtError> {
+        self.only_role(ROLE_ART_MANAGER)?;
+        self.canvas.set_cooldown(cooldown);
+        evm::log(PaintCooldownUpdated { cooldown });
+        Ok(())
+    }
+
+    /// Returns the color of the "Solid" background and the stops of the gradient backgrounds
+    pub fn get_background(&self) -> Result<(u32, u32, u32), StylusNftArtError> {
+        let config = self.art_config();
+        Ok((
+            config.background.to_hex() as u32,
+            config.gradient_start.to_hex() as u32,
+            config.gradient_end.to_hex() as u32,
+        ))
+    }
+
+    /// Returns the colors of a palette (1 = Warm, 2 = Cool, 3 = Mono)
+    pub fn get_palette(&self, palette: u8) -> Result<Vec<u32>, StylusNftArtError> {
+        let palette_index = (palette as usize).wrapping_sub(1);
+        let colors = self.palettes.getter(palette_index).ok_or(StylusNftArtError::InvalidPalette(InvalidPalette {
+            palette,
+            colors: U256::ZERO,
+        }))?;
+        Ok((0..colors.len()).map(|i| colors.get(i).unwrap_or_default().to::<u32>()).collect())
+    }
+
+    /// Returns the centers (x, y) of the left eye, right eye and mouth
+    pub fn get_feature_positions(&self) -> Result<(u8, u8, u8, u8, u8, u8), StylusNftArtError> {
+        let position = |index: usize| self.feature_positions.get(index).unwrap_or_default().to::<u8>();
+        Ok((position(0), position(1), position(2), position(3), position(4), position(5)))
+    }
+
+    /// Returns the relative weights used to pick each background, eyes, mouth and palette
+    pub fn get_rarity_weights(&self) -> Result<RarityWeightLists, StylusNftArtError> {
+        let weights = self.rarity_weights();
+        Ok((weights.background.to_vec(), weights.eyes.to_vec(), weights.mouth.to_vec(), weights.palette.to_vec()))
+    }
+
+    /// Getter for the admin address
+    pub fn get_admin(&self) -> Result<Address, StylusNftArtError> {
+        Ok(self.admin.get())
+    }
+
+    /// Starts transferring the admin to `new_admin`, which must accept it with `acceptAdmin`.
+    /// Transferring it to the zero address cancels a pending transfer.
+    pub fn transfer_admin(&mut self, new_admin: Address) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
+        self.pending_admin.set(new_admin);
+        evm::log(AdminTransferStarted { previous_admin: self.admin.get(), new_admin });
+        Ok(())
+    }
+
+    /// Accepts the admin transferred to the sender
+    pub fn accept_admin(&mut self) -> Result<(), StylusNftArtError> {
+        let sender = msg::sender();
+        if sender.is_zero() || sender != self.pending_admin.get() {
+            return Err(StylusNftArtError::NotAuthorized(NotAuthorized { account: sender }));
+        }
+        let previous_admin = self.admin.get();
+        self.admin.set(sender);
+        self.pending_admin.set(Address::ZERO);
+        evm::log(AdminTransferred { previous_admin, new_admin: sender });
+        Ok(())
+    }
+
+    /// Getter for the account the admin is being transferred to (zero if none)
+    pub fn get_pending_admin(&self) -> Result<Address, StylusNftArtError> {
+        Ok(self.pending_admin.get())
+    }
+
+    /// Grants a role (1 = art manager, 2 = collection manager) to an account
+    pub fn grant_role(&mut self, role: u8, account: Address) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
+        if !is_valid_role(role) {
+            return Err(StylusNftArtError::InvalidRole(InvalidRole { role }));
         }
+        let roles = self.roles.get(account).to::<u8>();
+        self.roles.insert(account, U8::from(roles | role));
+        evm::log(RoleGranted { role, account });
+        Ok(())
+    }
+
+    /// Revokes a role from an account
+    pub fn revoke_role(&mut self, role: u8, account: Address) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
+        if !is_valid_role(role) {
+            return Err(StylusNftArtError::InvalidRole(InvalidRole { role }));
+        }
+        let roles = self.roles.get(account).to::<u8>();
+        self.roles.insert(account, U8::from(roles & !role));
+        evm::log(RoleRevoked { role, account });
+        Ok(())
+    }
+
+    /// Returns whether an account has a role (the admin has all of them)
+    pub fn has_role(&self, role: u8, account: Address) -> Result<bool, StylusNftArtError> {
+        Ok(account == self.admin.get() || self.roles.get(account).to::<u8>() & role != 0)
+    }
+
+    /// Sets the NFT contract that this Art contract delivers the art for.
+    /// Its tokens keep the art versions, seeds and options recorded so far.
+    pub fn set_token_contract_address(&mut self, token_contract_address: Address) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
         self.token_contract_address.set(token_contract_address);
+        self.bump_cache_epoch();
+        evm::log(TokenContractAddressUpdated { token_contract_address });
+        Ok(())
+    }
+
+    /// Sets the ERC-20 contract whose balance of the owner selects the tier of the art
+    pub fn set_erc20_token_contract_address(&mut self, erc20_token_contract_address: Address) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
+        self.erc20_token_contract_address.set(erc20_token_contract_address);
+        evm::log(Erc20TokenContractAddressUpdated { erc20_token_contract_address });
         Ok(())
     }
 
+    /// Chooses whether the revert data of failed calls to the NFT, ERC-20 and registered collections is re-thrown
+    /// as is, instead of being wrapped in `ExternalCallFailed(target, selector, reason)`
+    pub fn set_forward_call_reverts(&mut self, forward: bool) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
+        self.forward_call_reverts.set(forward);
+        evm::log(CallRevertForwardingUpdated { forward });
+        Ok(())
+    }
+
+    /// Returns true if the revert data of failed calls is re-thrown as is
+    pub fn is_forwarding_call_reverts(&self) -> Result<bool, StylusNftArtError> {
+        Ok(self.forward_call_reverts.get())
+    }
+
+    /// Sets the gas given to each call to the NFT contract, the ERC-20 contract and the registered collections
+    /// (0 for all the gas left), so a misbehaving contract can't consume all the gas of the calls to this one
+    pub fn set_call_gas_limits(&mut self, nft: u64, erc20: u64, collection: u64) -> Result<(), StylusNftArtError> {
+        self.only_admin()?;
+        self.nft_call_gas_limit.set(U64::from(nft));
+        self.erc20_call_gas_limit.set(U64::from(erc20));
+        self.collection_call_gas_limit.set(U64::from(collection));
+        evm::log(CallGasLimitsUpdated { nft, erc20, collection });
+        Ok(())
+    }
+
+    /// Returns the gas given to each call to the NFT contract, the ERC-20 contract and the registered collections
+    /// (0 for all the gas left)
+    pub fn get_call_gas_limits(&self) -> Result<(u64, u64, u64), StylusNftArtError> {
+        Ok((
+            self.nft_call_gas_limit.get().to::<u64>(),
+            self.erc20_call_gas_limit.get().to::<u64>(),
+            self.collection_call_gas_limit.get().to::<u64>(),
+        ))
+    }
+
+    /// Getter for the ERC-20 contract address
+    pub fn get_erc20_token_contract_address(&self) -> Result<Address, StylusNftArtError> {
+        Ok(self.erc20_token_contract_address.get())
+    }
+
     /// Getter for the art contract address
     pub fn get_token_contract_address(&mut self) -> Result<Address, StylusNftArtError> {
         Ok(self.token_contract_address.get())
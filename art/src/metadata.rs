@@ -0,0 +1,143 @@
+//! ERC-721 metadata JSON document.
+use alloc::string::String;
+use base64::Engine;
+
+/// Appends `value` to `out` as a quoted JSON string, escaping it as needed
+pub fn push_json_string(out: &mut String, value: &str) {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // Other control characters must be escaped as \u00XX
+            c if (c as u32) < 0x20 => {
+                out.push_str("\\u00");
+                out.push(HEX_DIGITS[(c as usize) >> 4] as char);
+                out.push(HEX_DIGITS[(c as usize) & 0xf] as char);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Builds the metadata JSON document of a token
+pub fn make_metadata_json(
+    name: &str,
+    description: &str,
+    image_uri: &str,
+    attributes: &[(&str, &str)],
+) -> String {
+    let mut json = String::with_capacity(image_uri.len() + 512);
+    json.push_str("{\"name\":");
+    push_json_string(&mut json, name);
+    json.push_str(",\"description\":");
+    push_json_string(&mut json, description);
+    json.push_str(",\"image\":");
+    push_json_string(&mut json, image_uri);
+    json.push_str(",\"attributes\":[");
+    for (index, (trait_type, value)) in attributes.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"trait_type\":");
+        push_json_string(&mut json, trait_type);
+        json.push_str(",\"value\":");
+        push_json_string(&mut json, value);
+        json.push('}');
+    }
+    json.push_str("]}");
+    json
+}
+
+/// Returns the `data:` URI of a metadata JSON document
+pub fn make_metadata_uri(json: &str) -> String {
+    let mut uri = String::from("data:application/json;base64,");
+    base64::engine::general_purpose::STANDARD.encode_string(json, &mut uri);
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use serde_json::{json, Value};
+
+    /// Escapes `value` and parses it back with a reference JSON parser
+    fn round_trip(value: &str) -> Value {
+        let mut out = String::new();
+        push_json_string(&mut out, value);
+        serde_json::from_str(&out).unwrap_or_else(|err| panic!("{out} is not valid JSON: {err}"))
+    }
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        let mut out = String::new();
+        push_json_string(&mut out, r#"say "hi" \ bye"#);
+        assert_eq!(out, r#""say \"hi\" \\ bye""#);
+        assert_eq!(round_trip(r#"say "hi" \ bye"#), r#"say "hi" \ bye"#);
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        let mut out = String::new();
+        push_json_string(&mut out, "a\nb\rc\td\u{0}e\u{1f}f\u{7f}");
+        assert_eq!(out, "\"a\\nb\\rc\\td\\u0000e\\u001ff\u{7f}\"");
+        let all_controls: String = (0..0x20).map(|c| char::from_u32(c).unwrap()).collect();
+        assert_eq!(round_trip(&all_controls), all_controls.as_str());
+    }
+
+    #[test]
+    fn keeps_non_ascii_characters() {
+        let value = "Pépé 🎨 日本 \u{2028}";
+        let mut out = String::new();
+        push_json_string(&mut out, value);
+        assert_eq!(out, format!("\"{value}\""));
+        assert_eq!(round_trip(value), value);
+    }
+
+    #[test]
+    fn metadata_uri_decodes_to_the_document() {
+        let json = make_metadata_json(
+            "Stylus \"NFT\" #1",
+            "Line 1\nLine 2 \\ é",
+            "data:image/png;base64,iVBORw0KGgo=",
+            &[("Background", "Gradient"), ("Name", "\u{1}🎨")],
+        );
+        let uri = make_metadata_uri(&json);
+        let encoded = uri.strip_prefix("data:application/json;base64,").unwrap();
+        let decoded: Value = serde_json::from_slice(&STANDARD.decode(encoded).unwrap()).unwrap();
+        assert_eq!(
+            decoded,
+            json!({
+                "name": "Stylus \"NFT\" #1",
+                "description": "Line 1\nLine 2 \\ é",
+                "image": "data:image/png;base64,iVBORw0KGgo=",
+                "attributes": [
+                    { "trait_type": "Background", "value": "Gradient" },
+                    { "trait_type": "Name", "value": "\u{1}🎨" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn png_uri_decodes_to_the_image() {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0xff, 0x00];
+        let uri = crate::make_png_uri(&png);
+        let encoded = uri.strip_prefix("data:image/png;base64,").unwrap();
+        assert_eq!(STANDARD.decode(encoded).unwrap(), png);
+    }
+
+    #[test]
+    fn metadata_without_attributes_is_valid() {
+        let json = make_metadata_json("name", "", "", &[]);
+        let decoded: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded["attributes"], json!([]));
+    }
+}
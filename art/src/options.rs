@@ -0,0 +1,171 @@
+//! Options of the art of a token, chosen by its owner.
+use alloc::vec::Vec;
+use crate::art::{Cell, Color, Image};
+use crate::attributes::{Traits, BACKGROUNDS, PALETTES};
+use crate::config::ArtConfig;
+
+/// Names of the accessories, indexed by their bit in [`ArtOptions::accessories`]
+pub const ACCESSORIES: [&str; 2] = ["Glasses", "Blush"];
+
+/// Color of the glasses
+const GLASSES_COLOR: Color = Color::from_hex(0xffffff);
+
+/// Color of the blush
+const BLUSH_COLOR: Color = Color::from_hex(0xff69b4);
+
+/// Options of the art of a token.
+///
+/// They're encoded in up to 3 bytes, missing bytes being 0:
+/// - the palette (0 = the one picked from the seed, or the palette trait + 1)
+/// - the background (0 = the one picked from the seed, or the background trait + 1)
+/// - the accessories, as bit flags (bit 0 = Glasses, bit 1 = Blush)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArtOptions {
+    pub palette: Option<u8>,
+    pub background: Option<u8>,
+    pub accessories: u8,
+}
+
+impl ArtOptions {
+    /// Decodes the options, returning `None` if they're invalid
+    pub fn decode(bytes: &[u8]) -> Option<ArtOptions> {
+        if bytes.len() > 3 {
+            return None;
+        }
+        let byte = |index: usize| bytes.get(index).copied().unwrap_or_default();
+        let choice = |value: u8, choices: usize| match value {
+            0 => Some(None),
+            value if (value as usize) <= choices => Some(Some(value - 1)),
+            _ => None,
+        };
+        let accessories = byte(2);
+        if accessories >> ACCESSORIES.len() != 0 {
+            return None;
+        }
+        Some(ArtOptions {
+            palette: choice(byte(0), PALETTES.len())?,
+            background: choice(byte(1), BACKGROUNDS.len())?,
+            accessories,
+        })
+    }
+
+    /// Encodes the options, without the trailing zero bytes
+    pub fn encode(&self) -> Vec<u8> {
+        let choice = |value: Option<u8>| value.map_or(0, |value| value + 1);
+        let mut bytes = Vec::from([choice(self.palette), choice(self.background), self.accessories]);
+        while bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        bytes
+    }
+
+    /// Replaces the traits picked from the seed with the chosen ones
+    pub fn apply(&self, traits: &mut Traits) {
+        if let Some(palette) = self.palette {
+            traits.palette = palette;
+        }
+        if let Some(background) = self.background {
+            traits.background = background;
+        }
+    }
+
+    /// Returns the names of the chosen accessories
+    pub fn accessory_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        ACCESSORIES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.accessories & (1 << bit) != 0)
+            .map(|(_, &name)| name)
+    }
+
+    /// Draws the chosen accessories around the features positioned by `config`
+    pub fn draw_accessories(&self, image: &mut Image<32, 32>, config: &ArtConfig) {
+        // Keeps the accessories inside the image
+        let cell = |cell: Cell, dx: isize, dy: isize| {
+            let cell = cell.offset(dx, dy);
+            Cell::new(cell.x().min(31), cell.y().min(31))
+        };
+        if self.accessories & 1 != 0 {
+            for eye in [config.left_eye, config.right_eye] {
+                image.draw_line(cell(eye, -2, -2), cell(eye, 2, -2), GLASSES_COLOR);
+                image.draw_line(cell(eye, 2, -2), cell(eye, 2, 2), GLASSES_COLOR);
+                image.draw_line(cell(eye, 2, 2), cell(eye, -2, 2), GLASSES_COLOR);
+                image.draw_line(cell(eye, -2, 2), cell(eye, -2, -2), GLASSES_COLOR);
+            }
+            image.draw_line(cell(config.left_eye, 2, 0), cell(config.right_eye, -2, 0), GLASSES_COLOR);
+        }
+        if self.accessories & 2 != 0 {
+            for dx in [-5, 4] {
+                image.draw_line(cell(config.mouth, dx, -1), cell(config.mouth, dx + 1, -1), BLUSH_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_missing_bytes_as_zero() {
+        assert_eq!(ArtOptions::decode(&[]), Some(ArtOptions::default()));
+        assert_eq!(ArtOptions::decode(&[0, 0, 0]), Some(ArtOptions::default()));
+        assert_eq!(
+            ArtOptions::decode(&[2]),
+            Some(ArtOptions { palette: Some(1), ..ArtOptions::default() })
+        );
+        assert_eq!(
+            ArtOptions::decode(&[0, 1]),
+            Some(ArtOptions { background: Some(0), ..ArtOptions::default() })
+        );
+    }
+
+    #[test]
+    fn round_trips_all_valid_options() {
+        let choices = |count: usize| (0..=count as u8).map(|value| value.checked_sub(1));
+        for palette in choices(PALETTES.len()) {
+            for background in choices(BACKGROUNDS.len()) {
+                for accessories in 0..1u8 << ACCESSORIES.len() {
+                    let options = ArtOptions { palette, background, accessories };
+                    let bytes = options.encode();
+                    assert!(bytes.len() <= 3 && bytes.last() != Some(&0), "{options:?} encodes to {bytes:?}");
+                    assert_eq!(ArtOptions::decode(&bytes), Some(options));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encodes_without_trailing_zeros() {
+        assert_eq!(ArtOptions::default().encode(), [] as [u8; 0]);
+        assert_eq!(ArtOptions { palette: Some(0), ..ArtOptions::default() }.encode(), [1]);
+        assert_eq!(ArtOptions { accessories: 2, ..ArtOptions::default() }.encode(), [0, 0, 2]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_palettes() {
+        assert!(ArtOptions::decode(&[PALETTES.len() as u8]).is_some());
+        assert_eq!(ArtOptions::decode(&[PALETTES.len() as u8 + 1]), None);
+        assert_eq!(ArtOptions::decode(&[u8::MAX]), None);
+    }
+
+    #[test]
+    fn rejects_out_of_range_backgrounds() {
+        assert!(ArtOptions::decode(&[0, BACKGROUNDS.len() as u8]).is_some());
+        assert_eq!(ArtOptions::decode(&[0, BACKGROUNDS.len() as u8 + 1]), None);
+        assert_eq!(ArtOptions::decode(&[0, u8::MAX]), None);
+    }
+
+    #[test]
+    fn rejects_unknown_accessories() {
+        assert!(ArtOptions::decode(&[0, 0, (1 << ACCESSORIES.len()) - 1]).is_some());
+        assert_eq!(ArtOptions::decode(&[0, 0, 1 << ACCESSORIES.len()]), None);
+        assert_eq!(ArtOptions::decode(&[0, 0, u8::MAX]), None);
+    }
+
+    #[test]
+    fn rejects_more_than_3_bytes() {
+        assert_eq!(ArtOptions::decode(&[0, 0, 0, 0]), None);
+        assert_eq!(ArtOptions::decode(&[1, 1, 1, 0]), None);
+    }
+}
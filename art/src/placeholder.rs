@@ -0,0 +1,18 @@
+//! Placeholder shown instead of the art of the tokens of a collection that has not been revealed yet.
+use crate::art::{Cell, Image};
+use crate::config::ArtConfig;
+
+/// Description of the tokens in the metadata document of the placeholder
+pub const PLACEHOLDER_DESCRIPTION: &str = "This token has not been revealed yet";
+
+/// Draws a question mark over the placeholder background
+pub fn make_placeholder(config: &ArtConfig) -> Image<32, 32> {
+    let mut image = Image::new(config.placeholder_background);
+    let color = config.placeholder_foreground;
+    // The hook: the upper half and the lower right quarter of a circle
+    image.draw_ellipse(Cell::new(16, 11), 5, 5, [true, true, false, true], color);
+    image.draw_line(Cell::new(16, 16), Cell::new(16, 19), color);
+    // The dot
+    image.draw_line(Cell::new(16, 22), Cell::new(16, 23), color);
+    image
+}
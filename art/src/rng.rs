@@ -0,0 +1,233 @@
+//! Random number generators used to generate the art.
+//!
+//! Tokens minted with art version 3 or later use [`Xoshiro256StarStar`], which is fully specified here
+//! so that off-chain renderers (e.g. a TypeScript front-end) can reproduce the on-chain art exactly:
+//!
+//! - **Seeding**: the 32-byte seed of the token is split into four 8-byte words, each read as a
+//!   little-endian `u64`, which become the state `s[0..4]`. An all-zero seed is replaced by `s = [1, 0, 0, 0]`.
+//! - **Output**: each call to `next_u64` runs one step of [xoshiro256**](https://prng.di.unimi.it/xoshiro256starstar.c)
+//!   (`result = rotl(s[1] * 5, 7) * 9`, with wrapping multiplications).
+//! - **Bytes**: a random byte is the most significant byte of `next_u64` (`x >> 56`).
+//! - **Ranges**: a random integer in `0..n` (with `0 < n < 2^32`) is `((x >> 32) * n) >> 32`, where `x` is
+//!   `next_u64`. There is no rejection step, so a single output is consumed per call.
+//! - **Streams**: stream `k` of a seed is the generator seeded as above after calling `jump` `k` times.
+//!
+//! Test vectors (seed bytes `0x00, 0x01, ..., 0x1f`):
+//!
+//! | Call | Stream 0 `next_u64` | Stream 1 `next_u64` |
+//! |------|---------------------|---------------------|
+//! | 1    | `0xbc258ef861cb354d` | `0xbec273a99643df0d` |
+//! | 2    | `0x258ef861cb349eb5` | `0x5b03d3be02d90920` |
+//! | 3    | `0x013c0ee1b61e1c2d` | `0xc1ee627138a85faf` |
+//! | 4    | `0x60e9801651ad191d` | `0xec2f75bf36210c60` |
+//!
+//! With the same seed, stream 0, the first four random bytes are `188, 37, 1, 96` and the following
+//! four integers in `0..10` are `4, 5, 8, 6`.
+//!
+//! Tokens minted with earlier versions use `fastrand`'s wyrand, which is kept for backwards compatibility.
+//! Stream 0 of their 64-bit seed is seeded with the seed itself, and stream `k > 0` with the FNV-1a hash of
+//! the little-endian bytes of the seed followed by the little-endian bytes of `k` as a `u32`.
+use fastrand::Rng;
+
+use crate::utils::FnvHasher;
+
+/// Implements the xoshiro256** generator (not cryptographically secure)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xoshiro256StarStar {
+    s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    /// Creates a generator from a 32-byte seed
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let mut s = [0u64; 4];
+        for (word, bytes) in s.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        if s == [0; 4] {
+            s[0] = 1;
+        }
+        Xoshiro256StarStar { s }
+    }
+
+    /// Returns the next 64-bit output
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Advances the generator by 2^128 outputs, to get a non-overlapping stream
+    pub fn jump(&mut self) {
+        const JUMP: [u64; 4] = [
+            0x180ec6d33cfd0aba,
+            0xd5a61266f0c9392c,
+            0xa9582618e03fc9aa,
+            0x39abdc4529b1661c,
+        ];
+        let mut s = [0u64; 4];
+        for jump in JUMP {
+            for bit in 0..64 {
+                if jump & (1 << bit) != 0 {
+                    for (word, state) in s.iter_mut().zip(self.s) {
+                        *word ^= state;
+                    }
+                }
+                self.next_u64();
+            }
+        }
+        self.s = s;
+    }
+}
+
+/// Random number generator used by the art generators
+#[derive(Debug, Clone)]
+pub enum ArtRng {
+    /// `fastrand`'s wyrand (art versions 1 and 2)
+    Legacy(Rng),
+    /// xoshiro256** (art version 3 onwards)
+    Portable(Xoshiro256StarStar),
+}
+
+impl ArtRng {
+    /// Returns a random byte
+    pub fn u8(&mut self) -> u8 {
+        match self {
+            ArtRng::Legacy(rng) => rng.u8(..),
+            ArtRng::Portable(rng) => (rng.next_u64() >> 56) as u8,
+        }
+    }
+
+    /// Returns a random integer in `0..n`. `n` must be greater than 0
+    pub fn below(&mut self, n: u32) -> u32 {
+        match self {
+            ArtRng::Legacy(rng) => rng.u32(..n),
+            ArtRng::Portable(rng) => (((rng.next_u64() >> 32) * n as u64) >> 32) as u32,
+        }
+    }
+}
+
+/// Seed of the art of a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seed {
+    /// 64-bit seed of `fastrand`'s wyrand (art versions 1 and 2)
+    Legacy(u64),
+    /// 256-bit seed of xoshiro256** (art version 3 onwards)
+    Portable([u8; 32]),
+}
+
+impl Seed {
+    /// Creates the random number generator for stream `stream` of the seed.
+    /// Different streams are used for independent random decisions.
+    pub fn rng(&self, stream: u32) -> ArtRng {
+        match *self {
+            Seed::Legacy(seed) => ArtRng::Legacy(Rng::with_seed(legacy_stream_seed(seed, stream))),
+            Seed::Portable(seed) => {
+                let mut rng = Xoshiro256StarStar::from_seed(seed);
+                for _ in 0..stream {
+                    rng.jump();
+                }
+                ArtRng::Portable(rng)
+            }
+        }
+    }
+}
+
+/// Derives the wyrand seed of stream `stream` of a legacy seed. Stream 0 keeps the seed itself, so the art
+/// drawn from it doesn't change.
+fn legacy_stream_seed(seed: u64, stream: u32) -> u64 {
+    if stream == 0 {
+        return seed;
+    }
+    let mut hasher = FnvHasher::new();
+    hasher.update(&seed.to_le_bytes());
+    hasher.update(&stream.to_le_bytes());
+    hasher.output()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seed of the test vectors: bytes `0x00, 0x01, ..., 0x1f`
+    fn vector_seed() -> [u8; 32] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    fn next_u64s(rng: ArtRng) -> [u64; 4] {
+        let ArtRng::Portable(mut rng) = rng else {
+            panic!("not a portable generator");
+        };
+        [(); 4].map(|_| rng.next_u64())
+    }
+
+    #[test]
+    fn xoshiro_matches_the_test_vectors() {
+        let seed = Seed::Portable(vector_seed());
+        assert_eq!(
+            next_u64s(seed.rng(0)),
+            [0xbc258ef861cb354d, 0x258ef861cb349eb5, 0x013c0ee1b61e1c2d, 0x60e9801651ad191d]
+        );
+        assert_eq!(
+            next_u64s(seed.rng(1)),
+            [0xbec273a99643df0d, 0x5b03d3be02d90920, 0xc1ee627138a85faf, 0xec2f75bf36210c60]
+        );
+    }
+
+    #[test]
+    fn bytes_and_ranges_match_the_test_vectors() {
+        let mut rng = Seed::Portable(vector_seed()).rng(0);
+        assert_eq!([(); 4].map(|_| rng.u8()), [188, 37, 1, 96]);
+        assert_eq!([(); 4].map(|_| rng.below(10)), [4, 5, 8, 6]);
+    }
+
+    #[test]
+    fn all_zero_seed_is_replaced() {
+        assert_eq!(Xoshiro256StarStar::from_seed([0; 32]), Xoshiro256StarStar { s: [1, 0, 0, 0] });
+    }
+
+    #[test]
+    fn portable_streams_are_independent() {
+        let seed = Seed::Portable(vector_seed());
+        let streams: Vec<[u64; 4]> = (0..4).map(|stream| next_u64s(seed.rng(stream))).collect();
+        for (i, a) in streams.iter().enumerate() {
+            for b in &streams[i + 1..] {
+                assert!(a.iter().all(|x| !b.contains(x)), "streams share outputs: {a:x?} and {b:x?}");
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_stream_0_is_the_seed() {
+        let seed = 0x0123_4567_89ab_cdef;
+        let ArtRng::Legacy(mut rng) = Seed::Legacy(seed).rng(0) else {
+            panic!("not a legacy generator");
+        };
+        let mut expected = Rng::with_seed(seed);
+        assert_eq!([(); 4].map(|_| rng.u64(..)), [(); 4].map(|_| expected.u64(..)));
+    }
+
+    #[test]
+    fn legacy_streams_are_independent() {
+        for seed in [0, 1, 0x0123_4567_89ab_cdef, u64::MAX] {
+            let streams: Vec<[u8; 8]> = (0..8)
+                .map(|stream| {
+                    let mut rng = Seed::Legacy(seed).rng(stream);
+                    [(); 8].map(|_| rng.u8())
+                })
+                .collect();
+            for (i, a) in streams.iter().enumerate() {
+                for (j, b) in streams.iter().enumerate().skip(i + 1) {
+                    assert_ne!(a, b, "streams {i} and {j} of seed {seed:#x} are the same");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Stages unlocked by holding a token: decorations drawn over the art of any generator.
+use crate::art::{Cell, Color, Image};
+use crate::config::ArtConfig;
+
+/// Names of the stages, from the one of a token that was just transferred
+pub const STAGES: [&str; 4] = ["Fresh", "Settled", "Seasoned", "Legendary"];
+
+/// Highest stage a token can reach
+pub const MAX_STAGE: u8 = STAGES.len() as u8 - 1;
+
+/// Default number of seconds a token must be held to reach each stage after the first one
+pub const DEFAULT_STAGE_THRESHOLDS: [u64; MAX_STAGE as usize] = [
+    24 * 60 * 60,      // 1 day
+    7 * 24 * 60 * 60,  // 1 week
+    30 * 24 * 60 * 60, // 30 days
+];
+
+/// Color of the decorations
+const GOLD: Color = Color::from_hex(0xffd700);
+
+/// Returns the stage reached after holding a token for `held_for` seconds,
+/// given the number of seconds needed to reach each stage after the first one
+pub fn stage_for(held_for: u64, thresholds: &[u64]) -> u8 {
+    thresholds.iter().take(MAX_STAGE as usize).filter(|&&threshold| held_for >= threshold).count() as u8
+}
+
+/// Draws the decorations unlocked up to `stage`:
+/// a frame (stage 1), a crown above the eyes (stage 2) and sparkles in the corners (stage 3)
+pub fn draw_stage(image: &mut Image<32, 32>, stage: u8, config: &ArtConfig) {
+    if stage >= 1 {
+        image.draw_line(Cell::new(0, 0), Cell::new(31, 0), GOLD);
+        image.draw_line(Cell::new(31, 0), Cell::new(31, 31), GOLD);
+        image.draw_line(Cell::new(31, 31), Cell::new(0, 31), GOLD);
+        image.draw_line(Cell::new(0, 31), Cell::new(0, 0), GOLD);
+    }
+    if stage >= 2 {
+        // Centered between the eyes, 3 rows above them, and kept inside the frame
+        let x = ((config.left_eye.x() + config.right_eye.x()) / 2).clamp(4, 27);
+        let y = config.left_eye.y().min(config.right_eye.y()).saturating_sub(3).max(2);
+        image.draw_line(Cell::new(x - 3, y), Cell::new(x + 3, y), GOLD);
+        for tip in [x - 3, x, x + 3] {
+            image.draw_line(Cell::new(tip, y - 1), Cell::new(tip, y - 1), GOLD);
+        }
+    }
+    if stage >= 3 {
+        for (x, y) in [(3, 3), (28, 3), (3, 28), (28, 28)] {
+            let center = Cell::new(x, y);
+            image.draw_line(center.offset(-1, 0), center.offset(1, 0), GOLD);
+            image.draw_line(center.offset(0, -1), center.offset(0, 1), GOLD);
+        }
+    }
+}
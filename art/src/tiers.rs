@@ -0,0 +1,70 @@
+//! Tiers of the ERC-20 balance of the owner: rings drawn around the art of any generator.
+use stylus_sdk::alloy_primitives::{uint, U256};
+use crate::art::{Cell, Color, Image};
+
+/// Names of the tiers, from the one of an owner without enough ERC-20 tokens
+pub const TIERS: [&str; 4] = ["None", "Bronze", "Silver", "Diamond"];
+
+/// Highest tier an owner can reach
+pub const MAX_TIER: u8 = TIERS.len() as u8 - 1;
+
+/// Default number of whole ERC-20 tokens needed to reach each tier after the first one
+pub const DEFAULT_TIER_TOKENS: [U256; MAX_TIER as usize] = [uint!(10_U256), uint!(100_U256), uint!(1000_U256)];
+
+/// Decimals assumed for the ERC-20 token when the Art contract is initialized without one
+pub const DEFAULT_ERC20_DECIMALS: u8 = 18;
+
+/// Color of the ring of each tier after the first one
+const TIER_COLORS: [Color; MAX_TIER as usize] = [
+    Color::from_hex(0xcd7f32),
+    Color::from_hex(0xc0c0c0),
+    Color::from_hex(0xb9f2ff),
+];
+
+/// Returns the default minimum ERC-20 balance needed to reach each tier after the first one,
+/// in the smallest units of a token with `decimals` decimals
+pub fn default_tier_thresholds(decimals: u8) -> [U256; MAX_TIER as usize] {
+    let unit = U256::from(10).saturating_pow(U256::from(decimals));
+    DEFAULT_TIER_TOKENS.map(|tokens| tokens.saturating_mul(unit))
+}
+
+/// Returns the tier reached with an ERC-20 `balance`,
+/// given the minimum balance needed to reach each tier after the first one
+pub fn tier_for(balance: U256, thresholds: &[U256]) -> u8 {
+    thresholds.iter().take(MAX_TIER as usize).filter(|&&threshold| balance >= threshold).count() as u8
+}
+
+/// Draws a ring one pixel inside the edges of the image, in the color of the tier (nothing for the first tier)
+pub fn draw_tier(image: &mut Image<32, 32>, tier: u8) {
+    let Some(&color) = TIER_COLORS.get((tier as usize).wrapping_sub(1)) else {
+        return;
+    };
+    image.draw_line(Cell::new(1, 1), Cell::new(30, 1), color);
+    image.draw_line(Cell::new(30, 1), Cell::new(30, 30), color);
+    image.draw_line(Cell::new(30, 30), Cell::new(1, 30), color);
+    image.draw_line(Cell::new(1, 30), Cell::new(1, 1), color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_thresholds_are_whole_tokens() {
+        let thresholds = default_tier_thresholds(18);
+        assert_eq!(thresholds[0], uint!(10_000_000_000_000_000_000_U256));
+        assert_eq!(thresholds[2], uint!(1_000_000_000_000_000_000_000_U256));
+        assert_eq!(default_tier_thresholds(0), DEFAULT_TIER_TOKENS);
+        assert_eq!(default_tier_thresholds(u8::MAX), [U256::MAX; MAX_TIER as usize]);
+    }
+
+    #[test]
+    fn minting_balance_does_not_reach_the_first_tier() {
+        // Minting needs 10 units of the ERC-20 token, far less than 10 whole tokens
+        let thresholds = default_tier_thresholds(DEFAULT_ERC20_DECIMALS);
+        assert_eq!(tier_for(U256::from(10), &thresholds), 0);
+        assert_eq!(tier_for(thresholds[0], &thresholds), 1);
+        assert_eq!(tier_for(thresholds[1] - U256::from(1), &thresholds), 1);
+        assert_eq!(tier_for(U256::MAX, &thresholds), MAX_TIER);
+    }
+}
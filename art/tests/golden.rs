@@ -0,0 +1,57 @@
+//! Checks that the art of version 1 tokens is still drawn exactly like the original contract did.
+//!
+//! The images in `tests/golden` were rendered by the original contract, before generators, art versions and
+//! the art configuration were added. Their names are `v1-<owner>-<token id>.png`.
+use std::{fs, path::Path};
+
+use stylus_sdk::alloy_primitives::{Address, U256};
+use stylus_workshop_rust_nft_art::{
+    config::ArtConfig, gen_art, generators::LegacyGenerator, legacy_token_seed, options::ArtOptions, rng::Seed,
+    stages::MAX_STAGE, tiers::MAX_TIER,
+};
+
+/// Decodes a PNG file into its width, height and RGB bytes
+fn decode_png(path: &Path) -> (u32, u32, Vec<u8>) {
+    let decoder = png::Decoder::new(fs::File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut rgb = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut rgb).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgb);
+    rgb.truncate(info.buffer_size());
+    (info.width, info.height, rgb)
+}
+
+#[test]
+fn version_1_art_matches_the_original_renders() {
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut checked = 0;
+    for entry in fs::read_dir(golden).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let mut parts = name.strip_prefix("v1-").unwrap().split('-');
+        let owner: Address = parts.next().unwrap().parse().unwrap();
+        let token_id: U256 = parts.next().unwrap().parse().unwrap();
+
+        let seed = Seed::Legacy(legacy_token_seed(owner, token_id));
+        let (image, _) = gen_art(
+            &seed,
+            &LegacyGenerator,
+            &ArtConfig::default(),
+            &ArtOptions::default(),
+            None,
+            0,
+            0,
+        );
+        assert_eq!(decode_png(&path), (32, 32, image.make_rgb()), "pixels of {name}");
+        assert_eq!(image.make_png(), fs::read(&path).unwrap(), "PNG encoding of {name}");
+
+        // Nothing is drawn over the frozen art: not the tier of the holder, its stage or accessories
+        let options = ArtOptions { accessories: 0b11, ..ArtOptions::default() };
+        for (stage, tier, options) in [(0, 1, ArtOptions::default()), (MAX_STAGE, MAX_TIER, options)] {
+            let (overlaid, _) = gen_art(&seed, &LegacyGenerator, &ArtConfig::default(), &options, None, stage, tier);
+            assert_eq!(overlaid.make_png(), image.make_png(), "{name} at stage {stage} and tier {tier}");
+        }
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
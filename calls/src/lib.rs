@@ -0,0 +1,46 @@
+//! Calls between the contracts of the workshop: the gas given to them, and how their failures are reported.
+//!
+//! A failed call is reported as `ExternalCallFailed(target, selector, reason)`, or, if the calling contract
+//! was told so, by re-throwing the revert data of the called contract verbatim.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use alloy_sol_types::{sol, SolError};
+use stylus_sdk::{
+    alloy_primitives::Address,
+    call::{self, Call},
+};
+
+sol! {
+    /// A call to the function `selector` of the external contract `target` failed, reverting with `reason`
+    error ExternalCallFailed(address target, bytes4 selector, bytes reason);
+}
+
+/// Returns the revert data of a failed call (empty if the call succeeded but its result could not be decoded)
+pub fn revert_data(error: call::Error) -> Vec<u8> {
+    match error {
+        call::Error::Revert(data) => data,
+        call::Error::AbiDecodingFailed(_) => Vec::new(),
+    }
+}
+
+/// Returns the revert data a contract reverts with when its call to the function `selector` of `target` failed:
+/// the revert data of `target` itself if `forward` is true and there is any, or else `ExternalCallFailed` carrying it
+pub fn call_failed(target: Address, selector: [u8; 4], error: call::Error, forward: bool) -> Vec<u8> {
+    let reason = revert_data(error);
+    if forward && !reason.is_empty() {
+        return reason;
+    }
+    ExternalCallFailed {
+        target,
+        selector: selector.into(),
+        reason: reason.into(),
+    }
+    .abi_encode()
+}
+
+/// Returns the configuration of a call given at most `gas_limit` gas (all the gas left if 0)
+pub fn call_with_gas_limit(gas_limit: u64) -> Call<()> {
+    Call::new().gas(if gas_limit == 0 { u64::MAX } else { gas_limit })
+}
@@ -0,0 +1,231 @@
+//! Enumeration of the tokens held by each owner (like ERC721Enumerable)
+//!
+//! The tokens of an owner are kept in a list indexed from 0 to its balance - 1. Removing a token moves the last
+//! token of the list into the freed index, so both updates take a constant number of storage accesses.
+
+use alloc::vec::Vec;
+use stylus_sdk::alloy_primitives::{Address, U256};
+
+/// Storage of the lists of tokens of the owners, and of the index of each token in the list of its owner
+pub trait OwnedTokens {
+    /// Returns the token at `index` in the list of `owner`
+    fn owned_token(&self, owner: Address, index: U256) -> U256;
+
+    /// Stores the token at `index` in the list of `owner`
+    fn set_owned_token(&mut self, owner: Address, index: U256, token_id: U256);
+
+    /// Clears `index` in the list of `owner`
+    fn delete_owned_token(&mut self, owner: Address, index: U256);
+
+    /// Returns the index of a token in the list of its owner
+    fn owned_token_index(&self, token_id: U256) -> U256;
+
+    /// Stores the index of a token in the list of its owner
+    fn set_owned_token_index(&mut self, token_id: U256, index: U256);
+
+    /// Clears the index of a token
+    fn delete_owned_token_index(&mut self, token_id: U256);
+}
+
+/// Appends a token to the list of `owner`, whose `balance` doesn't count it yet
+pub fn add_owned_token(tokens: &mut impl OwnedTokens, owner: Address, balance: U256, token_id: U256) {
+    tokens.set_owned_token(owner, balance, token_id);
+    tokens.set_owned_token_index(token_id, balance);
+}
+
+/// Removes a token from the list of `owner`, whose `balance` still counts it,
+/// moving its last token into the freed index
+pub fn remove_owned_token(tokens: &mut impl OwnedTokens, owner: Address, balance: U256, token_id: U256) {
+    let last_index = balance - U256::from(1);
+    let index = tokens.owned_token_index(token_id);
+    if index != last_index {
+        let last_token_id = tokens.owned_token(owner, last_index);
+        tokens.set_owned_token(owner, index, last_token_id);
+        tokens.set_owned_token_index(last_token_id, index);
+    }
+    tokens.delete_owned_token(owner, last_index);
+    tokens.delete_owned_token_index(token_id);
+}
+
+/// Returns the list of tokens of `owner`, whose balance is `balance`
+pub fn owned_tokens(tokens: &impl OwnedTokens, owner: Address, balance: U256) -> Vec<U256> {
+    (0..balance.to::<u64>()).map(|index| tokens.owned_token(owner, U256::from(index))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use stylus_sdk::alloy_primitives::address;
+
+    const ALICE: Address = address!("A6E41fFD769491a42A6e5Ce453259b93983a22EF");
+    const BOB: Address = address!("3f1Eae7D46d88F08fc2F8ed27FCb2AB183EB2d0E");
+
+    /// In-memory ERC-721 ledger, updating the enumeration like `Erc721::transfer` does
+    #[derive(Default)]
+    struct Ledger {
+        owners: BTreeMap<U256, Address>,
+        balances: BTreeMap<Address, U256>,
+        owned_tokens: BTreeMap<(Address, U256), U256>,
+        owned_token_indexes: BTreeMap<U256, U256>,
+    }
+
+    impl OwnedTokens for Ledger {
+        fn owned_token(&self, owner: Address, index: U256) -> U256 {
+            self.owned_tokens.get(&(owner, index)).copied().unwrap_or_default()
+        }
+
+        fn set_owned_token(&mut self, owner: Address, index: U256, token_id: U256) {
+            self.owned_tokens.insert((owner, index), token_id);
+        }
+
+        fn delete_owned_token(&mut self, owner: Address, index: U256) {
+            self.owned_tokens.remove(&(owner, index));
+        }
+
+        fn owned_token_index(&self, token_id: U256) -> U256 {
+            self.owned_token_indexes.get(&token_id).copied().unwrap_or_default()
+        }
+
+        fn set_owned_token_index(&mut self, token_id: U256, index: U256) {
+            self.owned_token_indexes.insert(token_id, index);
+        }
+
+        fn delete_owned_token_index(&mut self, token_id: U256) {
+            self.owned_token_indexes.remove(&token_id);
+        }
+    }
+
+    impl Ledger {
+        fn balance(&self, owner: Address) -> U256 {
+            self.balances.get(&owner).copied().unwrap_or_default()
+        }
+
+        fn transfer(&mut self, token_id: u64, from: Address, to: Address) {
+            let token_id = U256::from(token_id);
+            assert_eq!(self.owners.get(&token_id).copied().unwrap_or_default(), from);
+            if !from.is_zero() {
+                remove_owned_token(self, from, self.balance(from), token_id);
+                self.balances.insert(from, self.balance(from) - U256::from(1));
+            }
+            if !to.is_zero() {
+                add_owned_token(self, to, self.balance(to), token_id);
+                self.balances.insert(to, self.balance(to) + U256::from(1));
+                self.owners.insert(token_id, to);
+            } else {
+                self.owners.remove(&token_id);
+            }
+        }
+
+        fn mint(&mut self, token_id: u64, to: Address) {
+            self.transfer(token_id, Address::ZERO, to);
+        }
+
+        fn burn(&mut self, token_id: u64, from: Address) {
+            self.transfer(token_id, from, Address::ZERO);
+        }
+
+        /// Returns the tokens of `owner` (`tokensOfOwner`)
+        fn tokens_of(&self, owner: Address) -> Vec<u64> {
+            owned_tokens(self, owner, self.balance(owner)).iter().map(|id| id.to::<u64>()).collect()
+        }
+
+        /// Checks that each listed token is at its recorded index, and that nothing is stored past the balances
+        fn assert_consistent(&self) {
+            for owner in [ALICE, BOB] {
+                for (index, token_id) in owned_tokens(self, owner, self.balance(owner)).into_iter().enumerate() {
+                    assert_eq!(self.owners[&token_id], owner);
+                    assert_eq!(self.owned_token_index(token_id), U256::from(index));
+                }
+            }
+            let listed: usize = [ALICE, BOB].iter().map(|&owner| self.balance(owner).to::<usize>()).sum();
+            assert_eq!(self.owned_tokens.len(), listed);
+            assert_eq!(self.owned_token_indexes.len(), listed);
+        }
+    }
+
+    /// Mints tokens 0 to `count` - 1 to Alice
+    fn minted(count: u64) -> Ledger {
+        let mut ledger = Ledger::default();
+        for token_id in 0..count {
+            ledger.mint(token_id, ALICE);
+        }
+        ledger
+    }
+
+    #[test]
+    fn lists_the_minted_tokens_in_order() {
+        let ledger = minted(4);
+        assert_eq!(ledger.tokens_of(ALICE), [0, 1, 2, 3]);
+        assert_eq!(ledger.owned_token(ALICE, U256::from(2)), U256::from(2));
+        ledger.assert_consistent();
+    }
+
+    #[test]
+    fn transfer_from_the_middle_moves_the_last_token() {
+        let mut ledger = minted(5);
+        ledger.transfer(1, ALICE, BOB);
+        assert_eq!(ledger.tokens_of(ALICE), [0, 4, 2, 3]);
+        assert_eq!(ledger.tokens_of(BOB), [1]);
+        assert_eq!(ledger.owned_token(ALICE, U256::from(1)), U256::from(4));
+        ledger.assert_consistent();
+
+        // The moved token can be removed from its new index
+        ledger.transfer(4, ALICE, BOB);
+        assert_eq!(ledger.tokens_of(ALICE), [0, 3, 2]);
+        assert_eq!(ledger.tokens_of(BOB), [1, 4]);
+        ledger.assert_consistent();
+    }
+
+    #[test]
+    fn burn_from_the_middle_moves_the_last_token() {
+        let mut ledger = minted(4);
+        ledger.burn(2, ALICE);
+        assert_eq!(ledger.tokens_of(ALICE), [0, 1, 3]);
+        ledger.burn(0, ALICE);
+        assert_eq!(ledger.tokens_of(ALICE), [3, 1]);
+        ledger.assert_consistent();
+    }
+
+    #[test]
+    fn removing_the_last_or_only_token_moves_nothing() {
+        let mut ledger = minted(3);
+        ledger.transfer(2, ALICE, BOB);
+        assert_eq!(ledger.tokens_of(ALICE), [0, 1]);
+        ledger.burn(1, ALICE);
+        ledger.burn(0, ALICE);
+        assert_eq!(ledger.tokens_of(ALICE), [] as [u64; 0]);
+        ledger.burn(2, BOB);
+        assert_eq!(ledger.tokens_of(BOB), [] as [u64; 0]);
+        ledger.assert_consistent();
+    }
+
+    #[test]
+    fn tokens_come_back_at_the_end() {
+        let mut ledger = minted(3);
+        ledger.transfer(0, ALICE, BOB);
+        ledger.transfer(0, BOB, ALICE);
+        assert_eq!(ledger.tokens_of(ALICE), [2, 1, 0]);
+        assert_eq!(ledger.tokens_of(BOB), [] as [u64; 0]);
+        ledger.assert_consistent();
+    }
+
+    #[test]
+    fn stays_consistent_over_many_transfers() {
+        let mut ledger = minted(16);
+        let mut state = 0x2545f4914f6cdd1du64;
+        for _ in 0..500 {
+            // xorshift, to pick the tokens to move
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let token_id = state % 16;
+            match ledger.owners.get(&U256::from(token_id)).copied() {
+                Some(owner) if state % 7 == 0 => ledger.burn(token_id, owner),
+                Some(owner) => ledger.transfer(token_id, owner, if owner == ALICE { BOB } else { ALICE }),
+                None => ledger.mint(token_id, if state % 2 == 0 { ALICE } else { BOB }),
+            }
+            ledger.assert_consistent();
+        }
+    }
+}
@@ -12,12 +12,14 @@ use alloc::vec;
 use core::{borrow::BorrowMut, marker::PhantomData};
 use stylus_sdk::{
     abi::Bytes,
+    block,
     evm,
     msg,
     prelude::*,
     alloy_primitives::{Address, FixedBytes, U256}
 };
 use alloy_sol_types::sol;
+use crate::enumeration::{add_owned_token, owned_tokens, remove_owned_token, OwnedTokens};
 
 pub trait Erc721Params {
     /// Immutable NFT name.
@@ -40,6 +42,14 @@ sol_storage! {
         mapping(address => mapping(address => bool)) operator_approvals;
         /// Total supply
         uint256 total_supply;
+        /// Token id to the account it was minted to
+        mapping(uint256 => address) minters;
+        /// Token id to the timestamp of its last transfer (including mints)
+        mapping(uint256 => uint256) last_transfers;
+        /// Owner to the token ids it holds, indexed from 0 to its balance - 1
+        mapping(address => mapping(uint256 => uint256)) owned_tokens;
+        /// Token id to its index in the tokens of its owner
+        mapping(uint256 => uint256) owned_token_indexes;
         /// Used to allow [`Erc721Params`]
         PhantomData<T> phantom;
     }
@@ -61,6 +71,8 @@ sol! {
     error TransferToZero(uint256 token_id);
     // The receiver address refused to receive the specified token id
     error ReceiverRefused(address receiver, uint256 token_id, bytes4 returned);
+    // The owner holds fewer tokens than the specified index
+    error OwnerIndexOutOfBounds(address owner, uint256 index);
 }
 
 /// Represents the ways methods may fail.
@@ -71,6 +83,7 @@ pub enum Erc721Error {
     NotApproved(NotApproved),
     TransferToZero(TransferToZero),
     ReceiverRefused(ReceiverRefused),
+    OwnerIndexOutOfBounds(OwnerIndexOutOfBounds),
 }
 
 // External interfaces
@@ -88,7 +101,7 @@ const ERC721_TOKEN_RECEIVER_ID: u32 = 0x150b7a02;
 // Methods marked as "pub" here are usable outside of the erc721 module (i.e. they're callable from lib.rs).
 impl<T: Erc721Params> Erc721<T> {
     /// Requires that msg::sender() is authorized to spend a given token
-    fn require_authorized_to_spend(
+    pub fn require_authorized_to_spend(
         &self,
         from: Address,
         token_id: U256,
@@ -146,6 +159,14 @@ impl<T: Erc721Params> Erc721<T> {
         }
         owner.set(to);
 
+        // the token enumeration uses the balances before updating them
+        if !from.is_zero() {
+            remove_owned_token(self, from, self.balances.get(from), token_id);
+        }
+        if !to.is_zero() {
+            add_owned_token(self, to, self.balances.get(to), token_id);
+        }
+
         // right now working with storage can be verbose, but this will change upcoming version of the Stylus SDK
         let mut from_balance = self.balances.setter(from);
         let balance = from_balance.get() - U256::from(1);
@@ -158,6 +179,8 @@ impl<T: Erc721Params> Erc721<T> {
         // cleaning app the approved mapping for this token
         self.token_approvals.delete(token_id);
 
+        self.last_transfers.insert(token_id, U256::from(block::timestamp()));
+
         evm::log(Transfer { from, to, token_id });
         Ok(())
     }
@@ -207,25 +230,27 @@ impl<T: Erc721Params> Erc721<T> {
         Self::call_receiver(storage, token_id, from, to, data)
     }
 
-    /// Mints a new token and transfers it to `to`
-    pub fn mint(&mut self, to: Address) -> Result<(), Erc721Error> {
+    /// Mints a new token and transfers it to `to`. Returns the id of the new token
+    pub fn mint(&mut self, to: Address) -> Result<U256, Erc721Error> {
         let new_token_id = self.total_supply.get();
         self.total_supply.set(new_token_id + U256::from(1u8));
+        self.minters.insert(new_token_id, to);
         self.transfer(new_token_id, Address::default(), to)?;
-        Ok(())
+        Ok(new_token_id)
     }
 
-    /// Mints a new token, and safe_transfers it to `to`
+    /// Mints a new token, and safe_transfers it to `to`. Returns the id of the new token
     pub fn safe_mint<S: TopLevelStorage + BorrowMut<Self>>(
         storage: &mut S,
         to: Address,
         data: Vec<u8>,
-    ) -> Result<(), Erc721Error> {
+    ) -> Result<U256, Erc721Error> {
         let this = storage.borrow_mut();
         let new_token_id = this.total_supply.get();
         this.total_supply.set(new_token_id + U256::from(1u8));
+        this.minters.insert(new_token_id, to);
         Self::safe_transfer(storage, new_token_id, Address::default(), to, data)?;
-        Ok(())
+        Ok(new_token_id)
     }
 
     /// Burns the token `token_id` from `from`
@@ -234,6 +259,37 @@ impl<T: Erc721Params> Erc721<T> {
         self.transfer(token_id, from, Address::default())?;
         Ok(())
     }
+
+    /// Returns the id of the next token to mint (token ids are never reused, even after burns)
+    pub fn next_token_id(&self) -> U256 {
+        self.total_supply.get()
+    }
+}
+
+impl<T: Erc721Params> OwnedTokens for Erc721<T> {
+    fn owned_token(&self, owner: Address, index: U256) -> U256 {
+        self.owned_tokens.getter(owner).get(index)
+    }
+
+    fn set_owned_token(&mut self, owner: Address, index: U256, token_id: U256) {
+        self.owned_tokens.setter(owner).insert(index, token_id);
+    }
+
+    fn delete_owned_token(&mut self, owner: Address, index: U256) {
+        self.owned_tokens.setter(owner).delete(index);
+    }
+
+    fn owned_token_index(&self, token_id: U256) -> U256 {
+        self.owned_token_indexes.get(token_id)
+    }
+
+    fn set_owned_token_index(&mut self, token_id: U256, index: U256) {
+        self.owned_token_indexes.insert(token_id, index);
+    }
+
+    fn delete_owned_token_index(&mut self, token_id: U256) {
+        self.owned_token_indexes.delete(token_id);
+    }
 }
 
 // these methods are public to other contracts
@@ -263,6 +319,32 @@ impl<T: Erc721Params> Erc721<T> {
         Ok(owner)
     }
 
+    /// Gets the token id at `index` in the tokens held by `owner` (like ERC721Enumerable).
+    pub fn token_of_owner_by_index(&self, owner: Address, index: U256) -> Result<U256, Erc721Error> {
+        if index >= self.balances.get(owner) {
+            return Err(Erc721Error::OwnerIndexOutOfBounds(OwnerIndexOutOfBounds { owner, index }));
+        }
+        Ok(self.owned_token(owner, index))
+    }
+
+    /// Gets all the token ids held by `owner`.
+    pub fn tokens_of_owner(&self, owner: Address) -> Result<Vec<U256>, Erc721Error> {
+        Ok(owned_tokens(self, owner, self.balances.get(owner)))
+    }
+
+    /// Gets the account the NFT was minted to.
+    /// Returns the zero address for NFTs minted before minters were recorded.
+    pub fn minter_of(&self, token_id: U256) -> Result<Address, Erc721Error> {
+        self.owner_of(token_id)?;
+        Ok(self.minters.get(token_id))
+    }
+
+    /// Gets the timestamp of the last transfer of the NFT (its mint if it was never transferred).
+    pub fn last_transfer_of(&self, token_id: U256) -> Result<U256, Erc721Error> {
+        self.owner_of(token_id)?;
+        Ok(self.last_transfers.get(token_id))
+    }
+
     /// Transfers an NFT, but only after checking the `to` address can receive the NFT.
     /// It includes additional data for the receiver.
     #[selector(name = "safeTransferFrom")]
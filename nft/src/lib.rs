@@ -1,30 +1,53 @@
 extern crate alloc;
 
 // Modules and imports
+mod enumeration;
 mod erc721;
+mod reveal;
 
 /// Import the Stylus SDK along with alloy primitive types for use in our program.
 use stylus_sdk::{
     abi::Bytes,
-    call::Call,
+    block,
+    call::{self, Call},
     contract,
+    evm,
+    function_selector,
     msg,
     prelude::*,
-    alloy_primitives::{Address, uint, U256}
+    alloy_primitives::{address, Address, FixedBytes, uint, U256, U64}
 };
 use alloy_sol_types::sol;
-use crate::erc721::{Erc721, Erc721Params};
+use stylus_workshop_rust_calls::{call_with_gas_limit, revert_data, ExternalCallFailed};
+use crate::erc721::{Erc721, Erc721Error, Erc721Params};
+use crate::reveal::{reveal_block_after, reveal_seed, reveal_step, RevealStep};
 
 // Interfaces for the Art contract and the ERC20 contract
 sol_interface! {
     interface NftArt {
-        function initialize(address token_contract_address) external;
-        function generateArt(uint256 token_id, address owner) external returns(string);
+        function registerToken(uint256 token_id) external;
+        function setTokenSeed(uint256 token_id, bytes32 seed) external;
+        function generateArt(uint256 token_id, address owner) external view returns(string);
+        function generateMetadata(uint256 token_id, address owner) external view returns(string);
+        function invalidateCache(uint256 token_id) external;
+        function setTokenHolder(uint256 token_id, address holder, uint64 timestamp) external;
+        function generatePlaceholderArt() external view returns(string);
+        function generatePlaceholderMetadata(uint256 token_id) external view returns(string);
+        function generateArtBatch(uint256[] token_ids, address[] owners) external view returns(string[]);
+        function generateMetadataBatch(uint256[] token_ids, address[] owners) external view returns(string[]);
+        function setTokenOptions(uint256 token_id, bytes options) external;
+        function registerChild(uint256 token_id, uint256 parent_a, uint256 parent_b) external;
+    }
+
+    interface ArbSys {
+        function arbBlockNumber() external view returns(uint256);
+        function arbBlockHash(uint256 block_number) external view returns(bytes32);
     }
 
     interface ERC20 {
         function setNftContractAddress(address nft_token_contract_address) external;
         function balanceOf(address owner) external returns(uint256);
+        function transferFrom(address from, address to, uint256 value) external returns(bool);
     }
 }
 
@@ -39,48 +62,197 @@ impl Erc721Params for StylusNFTParams {
 // Define the entrypoint as a Solidity storage object. The sol_storage! macro
 // will generate Rust-equivalent structs with all fields mapped to Solidity-equivalent
 // storage slots and types.
+// The contract can't be upgraded in place: a new version is deployed as a new contract,
+// so the layout of its storage doesn't need to stay compatible with the previous versions.
 sol_storage! {
     #[entrypoint]
     struct StylusNFT {
         address art_contract_address;
         address erc20_token_contract_address;
+        /// Account allowed to configure the contract
+        address admin;
+        /// If true, tokenURI returns the image only instead of the metadata JSON document
+        bool image_only_token_uri;
+        /// Token id to the future block whose hash will be mixed into its seed (0 if nothing to reveal)
+        mapping(uint256 => uint256) reveal_blocks;
+        /// If true, the art is rendered for the account each token was minted to instead of its current owner
+        bool art_frozen_at_mint;
+        /// If true, tokenURI returns a placeholder until the collection is revealed
+        bool delayed_reveal;
+        /// Whether the admin has revealed the collection
+        bool collection_revealed;
+        /// Timestamp from which the collection is revealed, even if the admin didn't reveal it (0 if none)
+        uint256 collection_reveal_time;
+        /// Number of seconds a token must wait between two breedings
+        uint256 breeding_cooldown;
+        /// ERC-20 tokens paid to the admin for each breeding (0 if free)
+        uint256 breeding_fee;
+        /// Token id to the timestamp of the last time it was bred (0 if never)
+        mapping(uint256 => uint256) last_bred;
+        /// If true, the revert data of failed calls to other contracts is re-thrown as is, instead of in ExternalCallFailed
+        bool forward_call_reverts;
+        /// Gas given to each call to the Art contract (0 for all the gas left)
+        uint64 art_call_gas_limit;
+        /// Gas given to each call to the ERC-20 contract (0 for all the gas left)
+        uint64 erc20_call_gas_limit;
+        /// Returned by tokenURI instead of an error when the call to the Art contract fails (empty if none)
+        string fallback_token_uri;
+        /// Art contracts used by this contract, in the order they became active (the current one last)
+        ArtContractRecord[] art_contracts;
+        /// Token id of a bred token to the index + 1 of the record of the Art contract of its parents (0 if not bred)
+        mapping(uint256 => uint256) bred_art_records;
 
         #[borrow] // Allows erc721 to access MyToken's storage and make calls
         Erc721<StylusNFTParams> erc721;
     }
+
+    /// An Art contract used by this contract
+    pub struct ArtContractRecord {
+        address art_contract;
+        /// Block at which it became active
+        uint256 activation_block;
+        /// Id of the first token minted while it was active, the first one it renders
+        uint256 first_token_id;
+    }
 }
 
-// Declare Solidity error types
+// Declare events and Solidity error types
 sol! {
+    /// The response of tokenURI has been switched between image only and metadata JSON
+    event TokenUriModeUpdated(bool image_only);
+    /// The art has been switched between being rendered for the minter or for the current owner of each token
+    event ArtModeUpdated(bool frozen_at_mint);
+    /// The seed of a token will be revealed with the hash of `reveal_block`
+    event SeedCommitted(uint256 indexed token_id, uint256 reveal_block);
+    /// The seed of a token has been revealed
+    event SeedRevealed(uint256 indexed token_id, bytes32 seed);
+    /// The tokens show a placeholder until the collection is revealed, at `reveal_time` at the latest (if not 0)
+    event DelayedRevealEnabled(uint256 reveal_time);
+    /// The admin has revealed the collection
+    event CollectionRevealed();
+    /// A token has been minted by breeding two parent tokens
+    event Bred(uint256 indexed token_id, uint256 indexed parent_a, uint256 indexed parent_b);
+    /// The admin has changed the number of seconds a token must wait between two breedings
+    event BreedingCooldownUpdated(uint256 cooldown);
+    /// The admin has changed the ERC-20 fee of breeding
+    event BreedingFeeUpdated(uint256 fee);
+    /// The metadata of a token has changed (ERC-4906)
+    event MetadataUpdate(uint256 _tokenId);
+    /// The admin has chosen whether the revert data of failed calls to other contracts is re-thrown as is
+    event CallRevertForwardingUpdated(bool forward);
+    /// The admin has changed the gas given to each call to the Art and ERC-20 contracts
+    event CallGasLimitsUpdated(uint64 art, uint64 erc20);
+    /// The admin has changed the URI returned by tokenURI when the call to the Art contract fails
+    event FallbackTokenUriUpdated(string uri);
+    /// The admin has migrated the NFT to a new Art contract
+    event ArtContractUpdated(address indexed art_contract, uint256 activation_block, uint256 first_token_id);
+    /// The metadata of a range of tokens has changed (ERC-4906)
+    event BatchMetadataUpdate(uint256 _fromTokenId, uint256 _toTokenId);
+    /// The Art contract could not be told about the transfer or burn of a token, which went through anyway
+    event ArtNotificationFailed(uint256 indexed token_id, address art_contract, bytes4 selector, bytes reason);
+
     /// Contract has already been initialized
     error AlreadyInitialized();
+    /// The account is not allowed to perform this action
+    error NotAuthorized(address account);
     /// Minter does not have enough ERC-20 balance to mint an NFT
     error NotEnoughERC20Balance(uint256 balance, uint256 expected);
-    /// A call to an external contract failed
-    error ExternalCallFailed();
+    /// The token has no seed pending to be revealed
+    error NothingToReveal(uint256 token_id);
+    /// The block whose hash is mixed into the seed has not been produced yet
+    error RevealTooEarly(uint256 token_id, uint256 reveal_block);
+    /// Too many tokens were requested in a single call
+    error TooManyTokens(uint256 count, uint256 max);
+    /// The collection has not been revealed yet
+    error CollectionNotRevealed();
+    /// The collection has already been revealed
+    error CollectionAlreadyRevealed();
+    /// A token can't be bred with itself
+    error InvalidParents(uint256 parent_a, uint256 parent_b);
+    /// The token has been bred too recently
+    error BreedingCooldown(uint256 token_id, uint256 next_breeding_time);
+    /// The Art contract can't be the zero address nor the current one
+    error InvalidArtContract(address art_contract);
+    /// Tokens bound to different Art contracts can't be bred together
+    error ParentsArtContractMismatch(uint256 parent_a, address art_contract_a, uint256 parent_b, address art_contract_b);
 }
 
 /// Represents the ways methods may fail.
 #[derive(SolidityError)]
 pub enum StylusNFTError {
     AlreadyInitialized(AlreadyInitialized),
+    NotAuthorized(NotAuthorized),
     NotEnoughERC20Balance(NotEnoughERC20Balance),
     ExternalCallFailed(ExternalCallFailed),
+    NothingToReveal(NothingToReveal),
+    RevealTooEarly(RevealTooEarly),
+    TooManyTokens(TooManyTokens),
+    CollectionNotRevealed(CollectionNotRevealed),
+    CollectionAlreadyRevealed(CollectionAlreadyRevealed),
+    InvalidParents(InvalidParents),
+    BreedingCooldown(BreedingCooldown),
+    InvalidArtContract(InvalidArtContract),
+    ParentsArtContractMismatch(ParentsArtContractMismatch),
 }
 
 /// Minimum balance on ERC-20 tokens that the minter must have to mint an NFT
 const ERC20_MIN_BALANCE_TO_MINT: U256 = uint!(10_U256);
 
+/// Number of blocks between minting a token and the block whose hash is mixed into its seed
+
+/// Default number of seconds a token must wait between two breedings
+const DEFAULT_BREEDING_COOLDOWN: U256 = uint!(86400_U256);
+
+/// Maximum number of token URIs returned by a single call of tokenURIs
+const MAX_TOKEN_URIS_BATCH: usize = 20;
+
+/// Interface id of ERC-4906 (metadata update events)
+const IERC4906: u32 = 0x49064906;
+
+/// Address of the ArbSys precompile
+const ARB_SYS_ADDRESS: Address = address!("0000000000000000000000000000000000000064");
+
 // Helper, private functions
 impl StylusNFT {
+    /// Check that the sender is the admin of the contract
+    fn only_admin(&self) -> Result<(), StylusNFTError> {
+        let sender = msg::sender();
+        if sender != self.admin.get() {
+            return Err(StylusNFTError::NotAuthorized(NotAuthorized { account: sender }));
+        }
+        Ok(())
+    }
+
+    /// Returns the revert data of a failed call to the function `selector` of `target`: an `ExternalCallFailed` error
+    /// carrying the revert data of `target`, or that revert data itself if the admin chose to forward it
+    fn call_failed(&self, target: Address, selector: [u8; 4], error: call::Error) -> Vec<u8> {
+        stylus_workshop_rust_calls::call_failed(target, selector, error, self.forward_call_reverts.get())
+    }
+
+    /// Returns the configuration of a call to the Art contract, bounded by its gas limit
+    fn art_call(&self) -> Call<()> {
+        call_with_gas_limit(self.art_call_gas_limit.get().to::<u64>())
+    }
+
+    /// Returns the configuration of a call to the ERC-20 contract, bounded by its gas limit
+    fn erc20_call(&self) -> Call<()> {
+        call_with_gas_limit(self.erc20_call_gas_limit.get().to::<u64>())
+    }
+
+    /// Returns the URI returned by tokenURI when the call to the Art contract fails, if the admin has set one
+    fn fallback_uri(&self) -> Option<String> {
+        let uri = self.fallback_token_uri.get_string();
+        (!uri.is_empty()).then_some(uri)
+    }
+
     /// Check if the minter has enough balance of the ERC-20 token configured
-    fn user_has_enough_erc20_token_balance(&mut self, account: Address) -> Result<(), StylusNFTError> {
+    fn user_has_enough_erc20_token_balance(&mut self, account: Address) -> Result<(), Vec<u8>> {
         let erc20_token_contract_address = self.erc20_token_contract_address.get();
         let erc20_token_contract = ERC20::new(erc20_token_contract_address);
-        let config = Call::new();
+        let config = self.erc20_call();
         let account_balance = erc20_token_contract
             .balance_of(config, account)
-            .map_err(|_e| StylusNFTError::ExternalCallFailed(ExternalCallFailed {}))?;
+            .map_err(|e| self.call_failed(erc20_token_contract_address, function_selector!("balanceOf", Address), e))?;
 
         if account_balance < ERC20_MIN_BALANCE_TO_MINT {
             return Err(StylusNFTError::NotEnoughERC20Balance(
@@ -88,9 +260,223 @@ impl StylusNFT {
                     balance: account_balance,
                     expected: ERC20_MIN_BALANCE_TO_MINT,
                 },
-            ));
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Registers a newly minted token in its Art contract, so its art is generated with the current art version,
+    /// and commits to a future block whose hash will be mixed into its seed
+    fn register_token_art(&mut self, token_id: U256) -> Result<(), Vec<u8>> {
+        let art_contract_address = self.token_art_contract(token_id);
+        let art_contract = NftArt::new(art_contract_address);
+        let config = self.art_call();
+        art_contract
+            .register_token(config, token_id)
+            .map_err(|e| self.call_failed(art_contract_address, function_selector!("registerToken", U256), e))?;
+
+        self.commit_seed(token_id)
+    }
+
+    /// Whether the tokens show their art: always, unless delayed reveal is enabled and the collection
+    /// has not been revealed by the admin nor reached its reveal time
+    fn is_revealed(&self) -> bool {
+        let reveal_time = self.collection_reveal_time.get();
+        !self.delayed_reveal.get()
+            || self.collection_revealed.get()
+            || (!reveal_time.is_zero() && U256::from(block::timestamp()) >= reveal_time)
+    }
+
+    /// Returns the account the art of a token is rendered for: its minter if the art is frozen at mint
+    /// (and the minter was recorded), or its current owner otherwise
+    fn art_owner(&self, token_id: U256) -> Result<Address, Erc721Error> {
+        let owner = self.erc721.owner_of(token_id)?;
+        if self.art_frozen_at_mint.get() {
+            let minter = self.erc721.minter_of(token_id)?;
+            if !minter.is_zero() {
+                return Ok(minter);
+            }
+        }
+        Ok(owner)
+    }
+
+    /// Tells the Art contract who holds a token after a transfer, whose balance gives the tier of its art,
+    /// and when it was transferred, so its stage restarts from the first one
+    /// (this also invalidates the art cached for the previous owner).
+    /// It's best-effort: an Art contract that fails doesn't prevent the transfer.
+    fn after_transfer(&mut self, token_id: U256) -> Result<(), Vec<u8>> {
+        let holder = self.erc721.owner_of(token_id)?;
+        let timestamp = self.erc721.last_transfer_of(token_id)?;
+        let art_contract_address = self.token_art_contract(token_id);
+        let art_contract = NftArt::new(art_contract_address);
+        let config = self.art_call();
+        if let Err(e) = art_contract.set_token_holder(config, token_id, holder, timestamp.to::<u64>()) {
+            let selector = function_selector!("setTokenHolder", U256, Address, u64);
+            Self::art_notification_failed(token_id, art_contract_address, selector, e);
+        }
+        Ok(())
+    }
+
+    /// Reports that a notification of the Art contract about a token failed, without failing the caller
+    fn art_notification_failed(token_id: U256, art_contract: Address, selector: [u8; 4], error: call::Error) {
+        evm::log(ArtNotificationFailed {
+            token_id,
+            art_contract,
+            selector: selector.into(),
+            reason: revert_data(error).into(),
+        });
+    }
+
+    /// Returns the placeholder shown by tokenURI before the collection or the token is revealed
+    /// (or the fallback URI if the call fails)
+    fn placeholder_uri(&self, token_id: U256) -> Result<String, Vec<u8>> {
+        let art_contract_address = self.token_art_contract(token_id);
+        let art_contract = NftArt::new(art_contract_address);
+        let config = self.art_call();
+        let (selector, placeholder) = if self.image_only_token_uri.get() {
+            (function_selector!("generatePlaceholderArt"), art_contract.generate_placeholder_art(config))
+        } else {
+            (
+                function_selector!("generatePlaceholderMetadata", U256),
+                art_contract.generate_placeholder_metadata(config, token_id),
+            )
+        };
+        placeholder.or_else(|e| self.fallback_uri().ok_or_else(|| self.call_failed(art_contract_address, selector, e)))
+    }
+
+    /// Removes the cached image of a token from the Art contract, since it was rendered for its previous owner.
+    /// It's best-effort: an Art contract that fails doesn't prevent the burn.
+    fn invalidate_token_art(&mut self, token_id: U256) {
+        let art_contract_address = self.token_art_contract(token_id);
+        let art_contract = NftArt::new(art_contract_address);
+        let config = self.art_call();
+        if let Err(e) = art_contract.invalidate_cache(config, token_id) {
+            Self::art_notification_failed(token_id, art_contract_address, function_selector!("invalidateCache", U256), e);
         }
+    }
+
+    /// Checks that the sender can breed a token: it must own it or be approved by its owner,
+    /// and the token must not have been bred during the breeding cooldown
+    fn check_breedable(&self, token_id: U256) -> Result<(), Vec<u8>> {
+        let owner = self.erc721.owner_of(token_id)?;
+        self.erc721.require_authorized_to_spend(owner, token_id)?;
+        let next_breeding_time = self.next_breeding_time(token_id);
+        if U256::from(block::timestamp()) < next_breeding_time {
+            return Err(StylusNFTError::BreedingCooldown(BreedingCooldown { token_id, next_breeding_time }).into());
+        }
+        Ok(())
+    }
+
+    /// Returns the timestamp from which a token can be bred again (0 if it was never bred)
+    fn next_breeding_time(&self, token_id: U256) -> U256 {
+        let last_bred = self.last_bred.get(token_id);
+        if last_bred.is_zero() {
+            return U256::ZERO;
+        }
+        last_bred.saturating_add(self.breeding_cooldown.get())
+    }
+
+    /// Transfers the breeding fee from `account` to the admin. The account must have approved this contract.
+    fn pay_breeding_fee(&mut self, account: Address) -> Result<(), Vec<u8>> {
+        let fee = self.breeding_fee.get();
+        if fee.is_zero() {
+            return Ok(());
+        }
+        let erc20_token_contract_address = self.erc20_token_contract_address.get();
+        let erc20_token_contract = ERC20::new(erc20_token_contract_address);
+        let config = self.erc20_call();
+        let selector = function_selector!("transferFrom", Address, Address, U256);
+        let transferred = erc20_token_contract
+            .transfer_from(config, account, self.admin.get(), fee)
+            .map_err(|e| self.call_failed(erc20_token_contract_address, selector, e))?;
+        if !transferred {
+            return Err(StylusNFTError::ExternalCallFailed(ExternalCallFailed {
+                target: erc20_token_contract_address,
+                selector: selector.into(),
+                reason: Vec::new().into(),
+            })
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Returns the current block number, from the ArbSys precompile
+    fn current_block(&self) -> Result<U256, Vec<u8>> {
+        ArbSys::new(ARB_SYS_ADDRESS)
+            .arb_block_number(Call::new())
+            .map_err(|e| self.call_failed(ARB_SYS_ADDRESS, function_selector!("arbBlockNumber"), e))
+    }
+
+    /// Appends an Art contract to the history of the art contracts
+    fn record_art_contract(&mut self, art_contract: Address, activation_block: U256, first_token_id: U256) {
+        let mut record = self.art_contracts.grow();
+        record.art_contract.set(art_contract);
+        record.activation_block.set(activation_block);
+        record.first_token_id.set(first_token_id);
+    }
+
+    /// Returns the index of the record of the Art contract that renders a token: the one that was active when it
+    /// was minted, or the one of its parents for a bred token, unless all the tokens were rebound to a newer one
+    fn token_art_record(&self, token_id: U256) -> Option<usize> {
+        let bred_art_record = self.bred_art_records.get(token_id).to::<usize>();
+        (0..self.art_contracts.len()).rev().find(|&index| {
+            let first_token_id = self.art_contracts.getter(index).unwrap().first_token_id.get();
+            if bred_art_record != 0 {
+                index + 1 == bred_art_record || first_token_id.is_zero()
+            } else {
+                token_id >= first_token_id
+            }
+        })
+    }
+
+    /// Returns the Art contract that renders a token (see [`Self::token_art_record`])
+    fn token_art_contract(&self, token_id: U256) -> Address {
+        match self.token_art_record(token_id) {
+            Some(index) => self.art_contracts.getter(index).unwrap().art_contract.get(),
+            None => self.art_contract_address.get(),
+        }
+    }
+
+    /// Calls an Art contract to get the token URIs of some tokens (or the fallback URI if the call fails)
+    fn art_token_uris(
+        &self,
+        art_contract_address: Address,
+        token_ids: Vec<U256>,
+        owners: Vec<Address>,
+    ) -> Result<Vec<String>, Vec<u8>> {
+        let count = token_ids.len();
+        let art_contract = NftArt::new(art_contract_address);
+        let config = self.art_call();
+        let (selector, uris) = if self.image_only_token_uri.get() {
+            (
+                function_selector!("generateArtBatch", Vec<U256>, Vec<Address>),
+                art_contract.generate_art_batch(config, token_ids, owners),
+            )
+        } else {
+            (
+                function_selector!("generateMetadataBatch", Vec<U256>, Vec<Address>),
+                art_contract.generate_metadata_batch(config, token_ids, owners),
+            )
+        };
+        uris.or_else(|e| match self.fallback_uri() {
+            Some(uri) => Ok(vec![uri; count]),
+            None => Err(self.call_failed(art_contract_address, selector, e)),
+        })
+    }
+
+    /// Returns true if the seed of a token is still waiting to be revealed
+    fn reveal_pending(&self, token_id: U256) -> bool {
+        !self.reveal_blocks.get(token_id).is_zero()
+    }
 
+    /// Schedules the reveal of the seed of a token to a future block
+    fn commit_seed(&mut self, token_id: U256) -> Result<(), Vec<u8>> {
+        let current_block = self.current_block()?;
+        let reveal_block = reveal_block_after(current_block);
+        self.reveal_blocks.insert(token_id, reveal_block);
+        evm::log(SeedCommitted { token_id, reveal_block });
         Ok(())
     }
 }
@@ -102,21 +488,24 @@ impl StylusNFT {
     pub fn mint(&mut self) -> Result<(), Vec<u8>> {
         let minter = msg::sender();
         self.user_has_enough_erc20_token_balance(minter)?;
-        self.erc721.mint(minter)?;
+        let token_id = self.erc721.mint(minter)?;
+        self.register_token_art(token_id)?;
         Ok(())
     }
 
     /// Mints an NFT to the specified address, and does not call onErc712Received
     pub fn mint_to(&mut self, to: Address) -> Result<(), Vec<u8>> {
         self.user_has_enough_erc20_token_balance(to)?;
-        self.erc721.mint(to)?;
+        let token_id = self.erc721.mint(to)?;
+        self.register_token_art(token_id)?;
         Ok(())
     }
 
     /// Mints an NFT and calls onErc712Received with empty data
     pub fn safe_mint(&mut self, to: Address) -> Result<(), Vec<u8>> {
         self.user_has_enough_erc20_token_balance(to)?;
-        Erc721::safe_mint(self, to, Vec::new())?;
+        let token_id = Erc721::safe_mint(self, to, Vec::new())?;
+        self.register_token_art(token_id)?;
         Ok(())
     }
 
@@ -125,64 +514,460 @@ impl StylusNFT {
     pub fn safe_mint_with_data(&mut self, data: Bytes) -> Result<(), Vec<u8>> {
         let minter = msg::sender();
         self.user_has_enough_erc20_token_balance(minter)?;
-        Erc721::safe_mint(self, minter, data.0)?;
+        let token_id = Erc721::safe_mint(self, minter, data.0)?;
+        self.register_token_art(token_id)?;
+        Ok(())
+    }
+
+    /// Breeds two tokens owned by the sender (or that the sender is approved for), minting a child token to the sender.
+    ///
+    /// Both parents must have been revealed. The seed of the child is derived from the seeds of its parents and
+    /// its own seed, revealed like the one of any minted token, and its traits are inherited from its parents.
+    /// The breeding fee (if any) is paid in ERC-20 tokens, which the sender must have approved this contract to spend.
+    /// Both parents must be bound to the same Art contract, which holds their traits: the child is bound to it too.
+    pub fn breed(&mut self, parent_a: U256, parent_b: U256) -> Result<(), Vec<u8>> {
+        if parent_a == parent_b {
+            return Err(StylusNFTError::InvalidParents(InvalidParents { parent_a, parent_b }).into());
+        }
+        self.check_breedable(parent_a)?;
+        self.check_breedable(parent_b)?;
+        let art_record = self.token_art_record(parent_a);
+        let art_contract_address = self.token_art_contract(parent_a);
+        let art_contract_b = self.token_art_contract(parent_b);
+        if art_contract_address != art_contract_b {
+            return Err(StylusNFTError::ParentsArtContractMismatch(ParentsArtContractMismatch {
+                parent_a,
+                art_contract_a: art_contract_address,
+                parent_b,
+                art_contract_b,
+            })
+            .into());
+        }
+        let breeder = msg::sender();
+        self.pay_breeding_fee(breeder)?;
+
+        let token_id = self.erc721.mint(breeder)?;
+        if let Some(index) = art_record {
+            self.bred_art_records.insert(token_id, U256::from(index + 1));
+        }
+        self.register_token_art(token_id)?;
+        let art_contract = NftArt::new(art_contract_address);
+        art_contract
+            .register_child(self.art_call(), token_id, parent_a, parent_b)
+            .map_err(|e| self.call_failed(art_contract_address, function_selector!("registerChild", U256, U256, U256), e))?;
+
+        let now = U256::from(block::timestamp());
+        self.last_bred.insert(parent_a, now);
+        self.last_bred.insert(parent_b, now);
+        evm::log(Bred { token_id, parent_a, parent_b });
+        Ok(())
+    }
+
+    /// Sets the number of seconds a token must wait between two breedings
+    pub fn set_breeding_cooldown(&mut self, cooldown: U256) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.breeding_cooldown.set(cooldown);
+        evm::log(BreedingCooldownUpdated { cooldown });
         Ok(())
     }
 
+    /// Returns the number of seconds a token must wait between two breedings
+    pub fn get_breeding_cooldown(&self) -> Result<U256, StylusNFTError> {
+        Ok(self.breeding_cooldown.get())
+    }
+
+    /// Sets the ERC-20 tokens paid to the admin for each breeding (0 to make it free)
+    pub fn set_breeding_fee(&mut self, fee: U256) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.breeding_fee.set(fee);
+        evm::log(BreedingFeeUpdated { fee });
+        Ok(())
+    }
+
+    /// Returns the ERC-20 tokens paid to the admin for each breeding
+    pub fn get_breeding_fee(&self) -> Result<U256, StylusNFTError> {
+        Ok(self.breeding_fee.get())
+    }
+
+    /// Returns the timestamp from which a token can be bred again (0 if it was never bred)
+    pub fn get_next_breeding_time(&self, token_id: U256) -> Result<U256, StylusNFTError> {
+        Ok(self.next_breeding_time(token_id))
+    }
+
     /// Burns an NFT
     pub fn burn(&mut self, token_id: U256) -> Result<(), Vec<u8>> {
         // This function checks that msg::sender() owns the specified token_id
         self.erc721.burn(msg::sender(), token_id)?;
+        self.invalidate_token_art(token_id);
         Ok(())
     }
 
-    /// Returns the image for the NFT
-    #[selector(name = "tokenURI")]
-    pub fn token_uri(&mut self, token_id: U256) -> Result<String, Vec<u8>> {
+    // The transfer methods override the ones of Erc721 to tell the Art contract when the token was transferred
+
+    /// Transfers the NFT
+    pub fn transfer_from(&mut self, from: Address, to: Address, token_id: U256) -> Result<(), Vec<u8>> {
+        self.erc721.transfer_from(from, to, token_id)?;
+        self.after_transfer(token_id)?;
+        Ok(())
+    }
+
+    /// Transfers the NFT, but only after checking the `to` address can receive the NFT
+    pub fn safe_transfer_from(&mut self, from: Address, to: Address, token_id: U256) -> Result<(), Vec<u8>> {
+        Erc721::safe_transfer_from(self, from, to, token_id)?;
+        self.after_transfer(token_id)?;
+        Ok(())
+    }
+
+    /// Transfers the NFT, but only after checking the `to` address can receive the NFT,
+    /// including additional data for the receiver
+    #[selector(name = "safeTransferFrom")]
+    pub fn safe_transfer_from_with_data(&mut self, from: Address, to: Address, token_id: U256, data: Bytes) -> Result<(), Vec<u8>> {
+        Erc721::safe_transfer_from_with_data(self, from, to, token_id, data)?;
+        self.after_transfer(token_id)?;
+        Ok(())
+    }
+
+    /// Customises the art of a token: chooses its palette, background and accessories, encoded as described
+    /// in the `options` module of the Art contract. Empty options restore the art picked from the seed.
+    /// Only the owner of the token or an account approved by the owner can call it.
+    pub fn set_art_options(&mut self, token_id: U256, options: Bytes) -> Result<(), Vec<u8>> {
         let owner = self.erc721.owner_of(token_id)?;
-        let art_contract_address = self.art_contract_address.get();
+        self.erc721.require_authorized_to_spend(owner, token_id)?;
+        let art_contract_address = self.token_art_contract(token_id);
+        let art_contract = NftArt::new(art_contract_address);
+        let config = self.art_call();
+        art_contract
+            .set_token_options(config, token_id, options.0.into())
+            .map_err(|e| self.call_failed(art_contract_address, function_selector!("setTokenOptions", U256, Bytes), e))?;
+        evm::log(MetadataUpdate { _tokenId: token_id });
+        Ok(())
+    }
+
+    /// Whether the NFT supports a given standard. Overrides the one of Erc721 to add ERC-4906.
+    pub fn supports_interface(interface: FixedBytes<4>) -> Result<bool, Vec<u8>> {
+        if u32::from_be_bytes(interface.0) == IERC4906 {
+            return Ok(true);
+        }
+        Ok(Erc721::<StylusNFTParams>::supports_interface(interface)?)
+    }
+
+    /// Reveals the seed of a token once its reveal block has been produced. Anyone can call it.
+    ///
+    /// The seed is the keccak256 of this contract's address, the token id and the hash of the reveal block,
+    /// which was unknown when the token was minted. Block hashes are only available for the latest 256 blocks,
+    /// so if the reveal block is too old the seed is committed to a new future block instead (emitting
+    /// `SeedCommitted`), to be revealed once that block has been produced.
+    pub fn reveal(&mut self, token_id: U256) -> Result<(), Vec<u8>> {
+        if !self.is_revealed() {
+            return Err(StylusNFTError::CollectionNotRevealed(CollectionNotRevealed {}).into());
+        }
+        let reveal_block = self.reveal_blocks.get(token_id);
+        if reveal_block.is_zero() {
+            return Err(StylusNFTError::NothingToReveal(NothingToReveal { token_id }).into());
+        }
+
+        // Whether the block hash is still available only depends on the block number, so that a failing call
+        // (for instance, one given too little gas) can't be used to pick the seed
+        let current_block = self.current_block()?;
+        match reveal_step(current_block, reveal_block) {
+            RevealStep::TooEarly => {
+                return Err(StylusNFTError::RevealTooEarly(RevealTooEarly { token_id, reveal_block }).into());
+            }
+            // Nobody knows the hash of the new block yet, so the seed can't be computed in advance
+            RevealStep::Recommit(reveal_block) => {
+                self.reveal_blocks.insert(token_id, reveal_block);
+                evm::log(SeedCommitted { token_id, reveal_block });
+                return Ok(());
+            }
+            RevealStep::Reveal => {}
+        }
+        let arb_sys = ArbSys::new(ARB_SYS_ADDRESS);
+        let block_hash = arb_sys
+            .arb_block_hash(Call::new(), reveal_block)
+            .map_err(|e| self.call_failed(ARB_SYS_ADDRESS, function_selector!("arbBlockHash", U256), e))?;
+        let seed = reveal_seed(contract::address(), token_id, block_hash);
+
+        self.reveal_blocks.delete(token_id);
+        let art_contract_address = self.token_art_contract(token_id);
+        let art_contract = NftArt::new(art_contract_address);
+        art_contract
+            .set_token_seed(self.art_call(), token_id, seed)
+            .map_err(|e| self.call_failed(art_contract_address, function_selector!("setTokenSeed", U256, FixedBytes<32>), e))?;
+        evm::log(SeedRevealed { token_id, seed });
+        Ok(())
+    }
+
+    /// Returns the block whose hash will be mixed into the seed of a token (0 if nothing to reveal)
+    pub fn get_reveal_block(&self, token_id: U256) -> Result<U256, StylusNFTError> {
+        Ok(self.reveal_blocks.get(token_id))
+    }
+
+    /// Returns the metadata JSON document for the NFT, or only its image if so configured
+    /// (or the fallback URI, if the admin has set one, when the call to the Art contract fails).
+    /// Until the seed of the token is revealed, it returns the placeholder.
+    #[selector(name = "tokenURI")]
+    pub fn token_uri(&self, token_id: U256) -> Result<String, Vec<u8>> {
+        let owner = self.art_owner(token_id)?;
+        if !self.is_revealed() || self.reveal_pending(token_id) {
+            return self.placeholder_uri(token_id);
+        }
+        let art_contract_address = self.token_art_contract(token_id);
         let art_contract = NftArt::new(art_contract_address);
-        let config = Call::new();
-        let image = art_contract
-            .generate_art(config, token_id, owner)
-            .map_err(|_e| StylusNFTError::ExternalCallFailed(ExternalCallFailed {}))?;
+        let config = self.art_call();
+        let (selector, uri) = if self.image_only_token_uri.get() {
+            (function_selector!("generateArt", U256, Address), art_contract.generate_art(config, token_id, owner))
+        } else {
+            (function_selector!("generateMetadata", U256, Address), art_contract.generate_metadata(config, token_id, owner))
+        };
+        // A failing Art contract degrades to the fallback URI, if any, instead of taking tokenURI down
+        let uri = uri.or_else(|e| self.fallback_uri().ok_or_else(|| self.call_failed(art_contract_address, selector, e)))?;
+
+        Ok(uri)
+    }
+
+    /// Returns the token URIs of up to `MAX_TOKEN_URIS_BATCH` tokens in a single call, as tokenURI does
+    /// (with a single call to each of the art contracts that render them)
+    #[selector(name = "tokenURIs")]
+    pub fn token_uris(&self, token_ids: Vec<U256>) -> Result<Vec<String>, Vec<u8>> {
+        if token_ids.len() > MAX_TOKEN_URIS_BATCH {
+            return Err(StylusNFTError::TooManyTokens(TooManyTokens {
+                count: U256::from(token_ids.len()),
+                max: U256::from(MAX_TOKEN_URIS_BATCH),
+            }).into());
+        }
+        let mut owners = Vec::with_capacity(token_ids.len());
+        for &token_id in &token_ids {
+            owners.push(self.art_owner(token_id)?);
+        }
+        let art_contracts: Vec<Address> = token_ids.iter().map(|&token_id| self.token_art_contract(token_id)).collect();
+        let mut uris = vec![String::new(); token_ids.len()];
+        let mut pending = Vec::with_capacity(token_ids.len());
+        let collection_revealed = self.is_revealed();
+        for (index, &token_id) in token_ids.iter().enumerate() {
+            if !collection_revealed || self.reveal_pending(token_id) {
+                uris[index] = self.placeholder_uri(token_id)?;
+            } else {
+                pending.push(index);
+            }
+        }
+        while let Some(&first) = pending.first() {
+            // Tokens rendered by the same art contract are fetched together
+            let art_contract_address = art_contracts[first];
+            let (group, rest): (Vec<usize>, Vec<usize>) = pending
+                .into_iter()
+                .partition(|&index| art_contracts[index] == art_contract_address);
+            let group_uris = self.art_token_uris(
+                art_contract_address,
+                group.iter().map(|&index| token_ids[index]).collect(),
+                group.iter().map(|&index| owners[index]).collect(),
+            )?;
+            for (index, uri) in group.into_iter().zip(group_uris) {
+                uris[index] = uri;
+            }
+            pending = rest;
+        }
+
+        Ok(uris)
+    }
+
+    /// Returns the account the art of a token is rendered for
+    pub fn art_owner_of(&self, token_id: U256) -> Result<Address, Vec<u8>> {
+        Ok(self.art_owner(token_id)?)
+    }
+
+    /// Switches between rendering the art for the minter of each token (frozen at mint) or for its current owner
+    pub fn set_art_frozen_at_mint(&mut self, frozen_at_mint: bool) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.art_frozen_at_mint.set(frozen_at_mint);
+        evm::log(ArtModeUpdated { frozen_at_mint });
+        Ok(())
+    }
 
-        Ok(image)
+    /// Returns true if the art is rendered for the minter of each token instead of its current owner
+    pub fn is_art_frozen_at_mint(&self) -> Result<bool, StylusNFTError> {
+        Ok(self.art_frozen_at_mint.get())
+    }
+
+    /// Makes tokenURI return a placeholder until the admin reveals the collection, or until `reveal_time` if it's not 0.
+    /// The seeds of the tokens can't be revealed until then either: the first `reveal` of a token whose reveal block
+    /// got too old in the meantime commits it to a new future block.
+    pub fn enable_delayed_reveal(&mut self, reveal_time: U256) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        if self.collection_revealed.get() {
+            return Err(StylusNFTError::CollectionAlreadyRevealed(CollectionAlreadyRevealed {}));
+        }
+        self.delayed_reveal.set(true);
+        self.collection_reveal_time.set(reveal_time);
+        evm::log(DelayedRevealEnabled { reveal_time });
+        Ok(())
+    }
+
+    /// Reveals the collection, so tokenURI returns the art of the tokens.
+    /// The seeds of the tokens are then revealed one by one with `reveal`, which commits the tokens whose reveal
+    /// block got older than 256 blocks during the delayed reveal to a new future block, instead of scheduling
+    /// all of them here.
+    pub fn reveal_collection(&mut self) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        if self.collection_revealed.get() {
+            return Err(StylusNFTError::CollectionAlreadyRevealed(CollectionAlreadyRevealed {}));
+        }
+        self.collection_revealed.set(true);
+        evm::log(CollectionRevealed {});
+        Ok(())
+    }
+
+    /// Returns true if tokenURI returns the art of the tokens instead of a placeholder
+    pub fn is_collection_revealed(&self) -> Result<bool, StylusNFTError> {
+        Ok(self.is_revealed())
+    }
+
+    /// Returns the timestamp from which the collection is revealed (0 if none)
+    pub fn get_collection_reveal_time(&self) -> Result<U256, StylusNFTError> {
+        Ok(self.collection_reveal_time.get())
+    }
+
+    /// Switches tokenURI between returning only the image (legacy) or the metadata JSON document
+    pub fn set_image_only_token_uri(&mut self, image_only: bool) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.image_only_token_uri.set(image_only);
+        evm::log(TokenUriModeUpdated { image_only });
+        Ok(())
+    }
+
+    /// Returns true if tokenURI returns only the image
+    pub fn is_image_only_token_uri(&self) -> Result<bool, StylusNFTError> {
+        Ok(self.image_only_token_uri.get())
     }
 
     /// Initialize program
-    pub fn initialize(&mut self, art_contract_address: Address, erc20_token_contract_address: Address) -> Result<(), StylusNFTError> {
+    ///
+    /// The Art contract is initialized separately by its admin, since the account that initializes it becomes its admin.
+    pub fn initialize(&mut self, art_contract_address: Address, erc20_token_contract_address: Address) -> Result<(), Vec<u8>> {
         let current_art_contract = self.art_contract_address.get();
         if !current_art_contract.is_zero() {
-            return Err(StylusNFTError::AlreadyInitialized(AlreadyInitialized {}));
+            return Err(StylusNFTError::AlreadyInitialized(AlreadyInitialized {}).into());
         }
         self.art_contract_address.set(art_contract_address);
 
         let current_erc20_token_contract = self.erc20_token_contract_address.get();
         if !current_erc20_token_contract.is_zero() {
-            return Err(StylusNFTError::AlreadyInitialized(AlreadyInitialized {}));
+            return Err(StylusNFTError::AlreadyInitialized(AlreadyInitialized {}).into());
         }
         self.erc20_token_contract_address.set(erc20_token_contract_address);
-
-        // Initializing the Art contract
-        let art_contract = NftArt::new(art_contract_address);
-        let config = Call::new();
-        let _result = art_contract
-            .initialize(config, contract::address())
-            .map_err(|_e| StylusNFTError::ExternalCallFailed(ExternalCallFailed {}))?;
+        self.admin.set(msg::sender());
+        // New collections render the art for the minter, so it doesn't change on transfers
+        self.art_frozen_at_mint.set(true);
+        self.breeding_cooldown.set(DEFAULT_BREEDING_COOLDOWN);
+        let activation_block = self.current_block()?;
+        self.record_art_contract(art_contract_address, activation_block, U256::ZERO);
 
         // Initializing the ERC-20 contract
         let erc20_contract = ERC20::new(erc20_token_contract_address);
-        let config = Call::new();
-        let _result = erc20_contract
+        let config = self.erc20_call();
+        erc20_contract
             .set_nft_contract_address(config, contract::address())
-            .map_err(|_e| StylusNFTError::ExternalCallFailed(ExternalCallFailed {}))?;
+            .map_err(|e| self.call_failed(erc20_token_contract_address, function_selector!("setNftContractAddress", Address), e))?;
+
+        Ok(())
+    }
+
+    /// Chooses whether the revert data of failed calls to the Art and ERC-20 contracts is re-thrown as is,
+    /// instead of being wrapped in `ExternalCallFailed(target, selector, reason)`
+    pub fn set_forward_call_reverts(&mut self, forward: bool) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.forward_call_reverts.set(forward);
+        evm::log(CallRevertForwardingUpdated { forward });
+        Ok(())
+    }
+
+    /// Returns true if the revert data of failed calls is re-thrown as is
+    pub fn is_forwarding_call_reverts(&self) -> Result<bool, StylusNFTError> {
+        Ok(self.forward_call_reverts.get())
+    }
+
+    /// Sets the gas given to each call to the Art contract and to the ERC-20 contract (0 for all the gas left),
+    /// so a misbehaving contract can't consume all the gas of the calls to this one.
+    /// The limit of the Art contract must leave enough gas to register the tokens when they're minted.
+    pub fn set_call_gas_limits(&mut self, art: u64, erc20: u64) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.art_call_gas_limit.set(U64::from(art));
+        self.erc20_call_gas_limit.set(U64::from(erc20));
+        evm::log(CallGasLimitsUpdated { art, erc20 });
+        Ok(())
+    }
+
+    /// Returns the gas given to each call to the Art contract and to the ERC-20 contract (0 for all the gas left)
+    pub fn get_call_gas_limits(&self) -> Result<(u64, u64), StylusNFTError> {
+        Ok((self.art_call_gas_limit.get().to::<u64>(), self.erc20_call_gas_limit.get().to::<u64>()))
+    }
+
+    /// Sets the URI returned by tokenURI and tokenURIs, instead of an error, when the call to the Art contract
+    /// runs out of gas or reverts (empty to return the error)
+    pub fn set_fallback_token_uri(&mut self, uri: String) -> Result<(), StylusNFTError> {
+        self.only_admin()?;
+        self.fallback_token_uri.set_str(&uri);
+        evm::log(FallbackTokenUriUpdated { uri });
+        Ok(())
+    }
+
+    /// Returns the URI returned by tokenURI when the call to the Art contract fails (empty if none)
+    pub fn get_fallback_token_uri(&self) -> Result<String, StylusNFTError> {
+        Ok(self.fallback_token_uri.get_string())
+    }
+
+    /// Migrates the NFT to a new Art contract, which must already deliver the art for this contract.
+    ///
+    /// If `rebind_minted_tokens` is false, the tokens minted so far keep the art contract they had, which holds their
+    /// seeds, versions and options, and only the tokens minted from now on use the new one. Otherwise, every token
+    /// switches to the new art contract (e.g. to replace a broken one), which emits an ERC-4906 metadata update
+    /// for all of them.
+    pub fn set_art_contract_address(
+        &mut self,
+        art_contract_address: Address,
+        rebind_minted_tokens: bool,
+    ) -> Result<(), Vec<u8>> {
+        self.only_admin()?;
+        let current_art_contract = self.art_contract_address.get();
+        if art_contract_address.is_zero() || art_contract_address == current_art_contract {
+            return Err(StylusNFTError::InvalidArtContract(InvalidArtContract { art_contract: art_contract_address }).into());
+        }
+        let activation_block = self.current_block()?;
+        let next_token_id = self.erc721.next_token_id();
+        let first_token_id = if rebind_minted_tokens { U256::ZERO } else { next_token_id };
+        self.record_art_contract(art_contract_address, activation_block, first_token_id);
+        self.art_contract_address.set(art_contract_address);
+        evm::log(ArtContractUpdated { art_contract: art_contract_address, activation_block, first_token_id });
 
+        if rebind_minted_tokens && !next_token_id.is_zero() {
+            evm::log(BatchMetadataUpdate { _fromTokenId: U256::ZERO, _toTokenId: next_token_id - U256::from(1) });
+        }
         Ok(())
     }
 
+    /// Returns the art contracts used by this contract and the blocks at which they became active,
+    /// in the order they became active (the current one last)
+    pub fn get_art_contract_history(&self) -> Result<(Vec<Address>, Vec<U256>), StylusNFTError> {
+        Ok((0..self.art_contracts.len())
+            .map(|index| {
+                let record = self.art_contracts.getter(index).unwrap();
+                (record.art_contract.get(), record.activation_block.get())
+            })
+            .unzip())
+    }
+
+    /// Returns the art contract that renders a token
+    pub fn art_contract_of(&self, token_id: U256) -> Result<Address, Vec<u8>> {
+        self.erc721.owner_of(token_id)?;
+        Ok(self.token_art_contract(token_id))
+    }
+
     /// Getter for the art contract address
     pub fn get_art_contract_address(&mut self) -> Result<Address, StylusNFTError> {
         Ok(self.art_contract_address.get())
     }
+
+    /// Getter for the admin address
+    pub fn get_admin(&self) -> Result<Address, StylusNFTError> {
+        Ok(self.admin.get())
+    }
 }
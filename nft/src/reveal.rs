@@ -0,0 +1,118 @@
+//! Derivation of the seeds of the tokens, revealed after they are minted
+//!
+//! The seed of a token is derived from the hash of a block produced after it was minted (its reveal block).
+//! Block hashes are only available for the latest `BLOCK_HASH_HISTORY` blocks, so a reveal block that got too old
+//! is replaced by a new future block: the seed always depends on a block hash nobody knew when it was committed to.
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    alloy_primitives::{uint, Address, FixedBytes, U256},
+    crypto,
+};
+
+/// Number of latest blocks whose hash the ArbSys precompile returns
+pub const BLOCK_HASH_HISTORY: U256 = uint!(256_U256);
+
+/// Number of blocks between the commit to a reveal block and that block
+pub const REVEAL_DELAY_BLOCKS: U256 = uint!(5_U256);
+
+/// What revealing the seed of a token does at a given block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealStep {
+    /// The reveal block has not been produced yet
+    TooEarly,
+    /// The hash of the reveal block is available, so the seed can be revealed with it
+    Reveal,
+    /// The hash of the reveal block is no longer available, so the seed is committed to this new reveal block
+    Recommit(U256),
+}
+
+/// Returns the block a seed committed at `current_block` is revealed with
+pub fn reveal_block_after(current_block: U256) -> U256 {
+    current_block + REVEAL_DELAY_BLOCKS
+}
+
+/// Returns what revealing a seed committed to `reveal_block` does at `current_block`
+pub fn reveal_step(current_block: U256, reveal_block: U256) -> RevealStep {
+    if current_block <= reveal_block {
+        RevealStep::TooEarly
+    } else if current_block - reveal_block > BLOCK_HASH_HISTORY {
+        RevealStep::Recommit(reveal_block_after(current_block))
+    } else {
+        RevealStep::Reveal
+    }
+}
+
+/// Returns the seed of a token: the keccak256 of the contract address, the token id and the hash of its reveal block
+pub fn reveal_seed(contract: Address, token_id: U256, block_hash: FixedBytes<32>) -> FixedBytes<32> {
+    let mut seed_data = Vec::with_capacity(96);
+    seed_data.extend_from_slice(contract.into_word().as_slice());
+    seed_data.extend_from_slice(&token_id.to_be_bytes::<32>());
+    seed_data.extend_from_slice(block_hash.as_slice());
+    crypto::keccak(seed_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stylus_sdk::alloy_primitives::{address, b256};
+    use tiny_keccak::{Hasher, Keccak};
+
+    /// Stands in for the keccak256 of the Stylus host, which the tests don't run on
+    #[no_mangle]
+    unsafe extern "C" fn native_keccak256(bytes: *const u8, len: usize, output: *mut u8) {
+        let mut hasher = Keccak::v256();
+        hasher.update(core::slice::from_raw_parts(bytes, len));
+        hasher.finalize(&mut *(output as *mut [u8; 32]));
+    }
+
+    const CONTRACT: Address = address!("A6E41fFD769491a42A6e5Ce453259b93983a22EF");
+
+    #[test]
+    fn reveals_once_the_reveal_block_is_produced() {
+        let reveal_block = U256::from(1000);
+        assert_eq!(reveal_step(U256::from(999), reveal_block), RevealStep::TooEarly);
+        assert_eq!(reveal_step(reveal_block, reveal_block), RevealStep::TooEarly);
+        assert_eq!(reveal_step(U256::from(1001), reveal_block), RevealStep::Reveal);
+        assert_eq!(reveal_step(U256::from(1256), reveal_block), RevealStep::Reveal);
+    }
+
+    #[test]
+    fn expired_reveal_commits_to_a_future_block() {
+        let reveal_block = U256::from(1000);
+        let current_block = U256::from(1257);
+        let RevealStep::Recommit(new_reveal_block) = reveal_step(current_block, reveal_block) else {
+            panic!("the hash of block 1000 is no longer available at block 1257");
+        };
+        assert_eq!(new_reveal_block, current_block + REVEAL_DELAY_BLOCKS);
+        // The new block has not been produced yet, and can then be revealed with its hash
+        assert_eq!(reveal_step(current_block, new_reveal_block), RevealStep::TooEarly);
+        assert_eq!(reveal_step(new_reveal_block + U256::from(1), new_reveal_block), RevealStep::Reveal);
+    }
+
+    #[test]
+    fn token_minted_long_before_the_collection_reveal_gets_a_fresh_block() {
+        // Minted at block 100, and revealed once the collection is revealed, days later
+        let reveal_block = reveal_block_after(U256::from(100));
+        let collection_reveal_block = U256::from(1_000_000);
+        assert_eq!(
+            reveal_step(collection_reveal_block, reveal_block),
+            RevealStep::Recommit(reveal_block_after(collection_reveal_block))
+        );
+    }
+
+    #[test]
+    fn seed_depends_on_the_block_hash() {
+        let token_id = U256::from(7);
+        let block_hash = b256!("5f8f1ccaa8e1b3e3b2f0b4c9d6a2c7e3f1b0a9d8c7e6f5a4b3c2d1e0f9a8b7c6");
+        let mut seed_data = CONTRACT.into_word().to_vec();
+        seed_data.extend_from_slice(&token_id.to_be_bytes::<32>());
+        seed_data.extend_from_slice(block_hash.as_slice());
+        let seed = reveal_seed(CONTRACT, token_id, block_hash);
+        assert_eq!(seed, crypto::keccak(seed_data));
+
+        let other_hash = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+        assert_ne!(reveal_seed(CONTRACT, token_id, other_hash), seed);
+        assert_ne!(reveal_seed(CONTRACT, token_id + U256::from(1), block_hash), seed);
+    }
+}
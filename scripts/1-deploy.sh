@@ -34,8 +34,8 @@ echo "----------------------"
 # Move to nft folder
 cd art
 
-# Deploy contract
-cargo stylus deploy -e $RPC_URL --private-key $PRIVATE_KEY --no-verify > $ART_DEPLOYMENT_DATA_FILE
+# Deploy contract (only the deployer will be able to initialize it)
+ART_ADMIN=$ADDRESS cargo stylus deploy -e $RPC_URL --private-key $PRIVATE_KEY --no-verify > $ART_DEPLOYMENT_DATA_FILE
 
 # ----------------- #
 # Deployment of NFT #
@@ -37,7 +37,10 @@ echo "----------------------"
 echo "Initializing contracts"
 echo "----------------------"
 
-# Initialize NFT contract (will also initialize the Art contract)
+# Initialize Art contract (the deployer becomes its admin)
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ART_CONTRACT_ADDRESS "initialize(address, address)" $NFT_CONTRACT_ADDRESS $ERC20_CONTRACT_ADDRESS
+
+# Initialize NFT contract (will also initialize the ERC-20 contract)
 cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "initialize(address, address)" $ART_CONTRACT_ADDRESS $ERC20_CONTRACT_ADDRESS
 
 # Final result
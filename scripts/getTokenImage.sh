@@ -24,6 +24,14 @@ echo "Generating art..."
 nft_art_image=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "tokenURI(uint256) (string)" 0)
 echo "NFT image: $nft_art_image"
 
+# Extract the image from the metadata JSON document (unless tokenURI is configured to return only the image)
+if [[ $nft_art_image == *"data:application/json;base64,"* ]]
+then
+    nft_metadata=$(base64 -di <<< ${nft_art_image/"data:application/json;base64,"/})
+    echo "NFT metadata: $nft_metadata"
+    nft_art_image=$(grep -o '"image":"[^"]*"' <<< $nft_metadata | cut -d '"' -f 4)
+fi
+
 # Create a PNG file
 filename="images/tokenImage_0_$(date +%s).png"
 base64 -di <<< ${nft_art_image/"data:image/png;base64,"/} > $filename
@@ -0,0 +1,29 @@
+#!/bin/bash
+
+# ------------- #
+# Configuration #
+# ------------- #
+
+# Load variables from .env file
+set -o allexport
+source scripts/.env
+set +o allexport
+
+# -------------- #
+# Initial checks #
+# -------------- #
+if [ -z "$NFT_CONTRACT_ADDRESS" ] 
+then
+    echo "NFT_CONTRACT_ADDRESS is not set"
+    echo "You can run the script by setting the variables at the beginning: NFT_CONTRACT_ADDRESS=0x revealNft.sh"
+    exit 0
+fi
+
+# Reveal NFT
+# (the reveal block of the token must have been produced, a few blocks after minting it)
+echo "Revealing the seed of NFT 0..."
+reveal_block=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "getRevealBlock(uint256) (uint256)" 0)
+echo "Reveal block: $reveal_block"
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "reveal(uint256)" 0
+
+# NFT_CONTRACT_ADDRESS= ./scripts/revealNft.sh
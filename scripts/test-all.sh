@@ -67,12 +67,58 @@ echo "NFT owner after minting (token id 0): $nft_owner_after_minting"
 echo "ERC-20 balance after minting: $erc20_balance_after_minting"
 echo ""
 
+# Reveals the seed of an NFT
+# (on a local dev node blocks are only produced with new transactions, so we send empty ones until the reveal block
+# has been produced, a bounded number of times)
+MAX_REVEAL_ATTEMPTS=20
+reveal_nft() {
+    token_id=$1
+    attempts=0
+    reveal_block=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "getRevealBlock(uint256) (uint256)" $token_id)
+    # A reveal block older than 256 blocks is replaced by a new one, which is then waited for
+    while [ "$reveal_block" != "0" ]
+    do
+        while [ "$(cast block-number --rpc-url $RPC_URL)" -le "$reveal_block" ]
+        do
+            attempts=$((attempts + 1))
+            if [ $attempts -gt $MAX_REVEAL_ATTEMPTS ]
+            then
+                echo "The reveal block $reveal_block of NFT $token_id was not produced after $MAX_REVEAL_ATTEMPTS transactions"
+                exit 1
+            fi
+            cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ADDRESS > /dev/null
+        done
+        if ! cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "reveal(uint256)" $token_id > /dev/null
+        then
+            echo "NFT $token_id could not be revealed"
+            exit 1
+        fi
+        reveal_block=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "getRevealBlock(uint256) (uint256)" $token_id)
+    done
+    echo "NFT $token_id revealed"
+}
+
+# Reveal the seeds of the NFTs
+echo "Revealing NFTs..."
+for token_id in 0 1 2 3
+do
+    reveal_nft $token_id
+done
+echo ""
+
 # Generate art
 echo "Generating art..."
 nft_art_image=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "tokenURI(uint256) (string)" 0)
 echo "NFT image: $nft_art_image"
 echo ""
 
+# Customise the art
+echo "Customising the art (Warm palette, Gradient background, glasses and blush)..."
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "setArtOptions(uint256,bytes)" 0 0x020103
+art_options=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "getTokenOptions(uint256) (bytes)" 0)
+echo "Art options (token id 0): $art_options"
+echo ""
+
 # Transfering
 echo "Transfering NFTs..."
 cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "transferFrom(address,address,uint256)" $ADDRESS $RECEIVER_ADDRESS 0
@@ -84,7 +130,9 @@ balance_receiver_after_transfering=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_
 owner_after_transfering=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "ownerOf(uint256) (address)" 0)
 echo "Balance after transfering (minter): $balance_minter_after_transfering"
 echo "Balance after transfering (receiver): $balance_receiver_after_transfering"
+art_owner_after_transfering=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "artOwnerOf(uint256) (address)" 0)
 echo "Owner after transfering (token id 0): $owner_after_transfering"
+echo "Art rendered for (token id 0): $art_owner_after_transfering"
 echo ""
 
 # Generate art (again)
@@ -93,15 +141,105 @@ nft_art_image_after_transfering=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADD
 echo "NFT image (after transfering): $nft_art_image_after_transfering"
 echo ""
 
+# Bound the gas of the calls to the ERC-20 contract (the Art contract keeps all the gas left), with a fallback URI in case the calls to the Art contract fail
+echo "Setting the gas limits and the fallback URI of the NFT contract..."
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "setCallGasLimits(uint64,uint64)" 0 1000000
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "setFallbackTokenUri(string)" "ipfs://fallback.png"
+nft_call_gas_limits=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "getCallGasLimits() (uint64,uint64)")
+echo "Gas limits (Art, ERC-20): $nft_call_gas_limits"
+echo ""
+
+# Get the history of the art contracts
+echo "Getting the history of the art contracts of the NFT contract..."
+art_contract_history=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "getArtContractHistory() (address[],uint256[])")
+art_contract_of_token=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "artContractOf(uint256) (address)" 0)
+echo "Art contracts (addresses, activation blocks): $art_contract_history"
+echo "Art contract of token id 0: $art_contract_of_token"
+echo ""
+
+# Get several token URIs in a single call
+echo "Getting token URIs in a batch..."
+nft_token_uris=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "tokenURIs(uint256[]) (string[])" "[0]")
+echo "Token URIs: $nft_token_uris"
+echo ""
+
 # Generate art directly from the Art contract
 echo "Generating art from the Art contract..."
-raw_art_image=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "generateArt(uint256,address) (string)" 0 $RECEIVER_ADDRESS)
+raw_art_image=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "generateArt(uint256,address) (string)" 0 $art_owner_after_transfering)
 echo "Image (from the Art contract): $raw_art_image"
 echo ""
 
+# Cache the image of a token in the Art contract
+echo "Caching the image of token id 0 in the Art contract..."
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ART_CONTRACT_ADDRESS "setCacheEnabled(bool)" true
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ART_CONTRACT_ADDRESS "warmCache(uint256)" 0
+art_image_cached=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "isCached(uint256,address) (bool)" 0 $art_owner_after_transfering)
+echo "Image cached (token id 0): $art_image_cached"
+echo ""
+
+# Get the keccak256 of the PNG image from the Art contract
+echo "Getting the hash of the image from the Art contract..."
+art_png_hash=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "generateArtAs(uint256,address,uint8) (bytes)" 0 $art_owner_after_transfering 2)
+echo "keccak256 of the PNG image: $art_png_hash"
+echo ""
+
+# Get traits from the Art contract
+echo "Getting traits from the Art contract..."
+art_traits=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "getTraits(uint256,address) (uint8,uint8,uint8,uint8)" 0 $art_owner_after_transfering)
+echo "Traits (background, eyes, mouth, palette): $art_traits"
+echo ""
+
+# Get the balance tier of the owner from the Art contract
+echo "Getting the balance tier of the owner from the Art contract..."
+art_owner_tier=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "getOwnerTier(address) (uint8)" $art_owner_after_transfering)
+echo "Tier (0 = None, 1 = Bronze, 2 = Silver, 3 = Diamond): $art_owner_tier"
+echo ""
+
+# Get a preview of the collection from the Art contract
+echo "Getting a collection preview from the Art contract..."
+collection_preview=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "collectionPreview(uint256[],address[]) (string)" "[0]" "[$art_owner_after_transfering]")
+echo "Collection preview: $collection_preview"
+echo ""
+
+# Get the portrait of the minter from the Art contract
+echo "Getting the portrait of the minter from the Art contract..."
+owner_tokens=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "tokensOfOwner(address) (uint256[])" $ADDRESS)
+owner_portrait=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "ownerPortrait(address) (string)" $ADDRESS)
+echo "Tokens of the minter: $owner_tokens"
+echo "Portrait of the minter: $owner_portrait"
+echo ""
+
+# Serve the art of another collection (the NFT contract itself, as any ERC-721 contract)
+echo "Registering a collection in the Art contract..."
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ART_CONTRACT_ADDRESS "registerCollection(address,string)" $NFT_CONTRACT_ADDRESS "Partner"
+collection_art=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "generateArtFor(address,uint256) (string)" $NFT_CONTRACT_ADDRESS 0)
+echo "Image (as a registered collection): $collection_art"
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ART_CONTRACT_ADDRESS "setCollectionColors(address,bool,uint32,uint32,uint32)" $NFT_CONTRACT_ADDRESS true 0x1e1e2e 0x89b4fa 0xf5c2e7
+collection_art=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "generateArtFor(address,uint256) (string)" $NFT_CONTRACT_ADDRESS 0)
+echo "Image (with the colors of the collection): $collection_art"
+echo ""
+
+# Paint a pixel of the canvas
+echo "Painting a pixel of the canvas with token id 2..."
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ART_CONTRACT_ADDRESS "paintPixel(uint256,uint8,uint8,uint8)" 2 32 32 5
+canvas_pixel=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "getCanvasPixel(uint8,uint8) (uint8)" 32 32)
+canvas_image=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "canvasPNG() (string)")
+echo "Canvas pixel (32, 32): $canvas_pixel"
+echo "Canvas image: $canvas_image"
+echo ""
+
 # Mint NFT directly from ERC-20 contract
 echo "Minting NFT from the ERC-20 contract..."
 cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $ERC20_CONTRACT_ADDRESS "mintNft()"
 balance_after_minting_from_erc20=$(cast call --rpc-url $RPC_URL $NFT_CONTRACT_ADDRESS "balanceOf(address) (uint256)" $ADDRESS)
 echo "Balance after minting from ERC20: $balance_after_minting_from_erc20"
-echo ""
\ No newline at end of file
+echo ""
+# Breed two NFTs (the child is token id 5, since the ERC-20 contract minted token id 4)
+echo "Breeding NFTs 2 and 3..."
+cast send --rpc-url $RPC_URL --private-key $PRIVATE_KEY $NFT_CONTRACT_ADDRESS "breed(uint256,uint256)" 2 3
+reveal_nft 5
+child_parents=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "getTokenParents(uint256) (bool,uint256,uint256)" 5)
+child_traits=$(cast call --rpc-url $RPC_URL $ART_CONTRACT_ADDRESS "getTraits(uint256,address) (uint8,uint8,uint8,uint8)" 5 $ADDRESS)
+echo "Parents of the child (bred, parent a, parent b): $child_parents"
+echo "Traits of the child (background, eyes, mouth, palette): $child_traits"
+echo ""